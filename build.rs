@@ -6,11 +6,41 @@ use std::path;
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
+    let out_dir = path::PathBuf::from(env::var_os("OUT_DIR").expect("run within cargo"));
+
     // env::ARCH doesn't include full triplet, and AFAIK there isn't a nicer way of getting the full triplet
     // (see lib.rs for the rest of this hack)
-    let out = path::PathBuf::from(env::var_os("OUT_DIR").expect("run within cargo"))
-        .join("current_target.txt");
     let default_target = env::var("TARGET").expect("run as cargo build script");
-    let mut file = fs::File::create(out).unwrap();
-    file.write_all(default_target.as_bytes()).unwrap();
+    write(&out_dir.join("current_target.txt"), &default_target);
+
+    // `PROFILE` only distinguishes `debug` from `release`, so recover the actual profile from the
+    // profile's directory, `<target-dir>/[<triplet>/]<profile>/build/<pkg>-<hash>/out`
+    let profile_dir = out_dir
+        .ancestors()
+        .find(|p| p.file_name().is_some_and(|n| n == "build"))
+        .and_then(|p| p.parent())
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str());
+    let profile = match profile_dir {
+        Some("debug") => "dev".to_owned(),
+        Some(profile) => profile.to_owned(),
+        None => match env::var("PROFILE")
+            .expect("run as cargo build script")
+            .as_str()
+        {
+            "debug" => "dev".to_owned(),
+            profile => profile.to_owned(),
+        },
+    };
+    write(&out_dir.join("current_profile.txt"), &profile);
+
+    let opt_level = env::var("OPT_LEVEL").expect("run as cargo build script");
+    write(&out_dir.join("current_opt_level.txt"), &opt_level);
+
+    // The calling crate's features aren't captured: `CARGO_FEATURE_*` only lists escargot's own
+}
+
+fn write(path: &path::Path, content: &str) {
+    let mut file = fs::File::create(path).unwrap();
+    file.write_all(content.as_bytes()).unwrap();
 }
@@ -0,0 +1,207 @@
+//! Report diagnostics as CI annotations.
+//!
+//! Supports [GitHub Actions workflow commands][github] and
+//! [GitLab Code Quality reports][gitlab].
+//!
+//! [github]: https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions
+//! [gitlab]: https://docs.gitlab.com/ee/ci/testing/code_quality.html
+//!
+//! # Example
+//!
+//! ```rust
+//! # let target_dir = tempfile::TempDir::new().unwrap();
+//! let root = std::env::current_dir().unwrap().join("tests/testsuite/fixtures/warn");
+//! let annotator = escargot::annotation::Annotator::new(&root);
+//! let msgs = escargot::CargoBuild::new()
+//!     .manifest_path(root.join("Cargo.toml"))
+//!     .target_dir(target_dir.path())
+//!     .exec()
+//!     .unwrap();
+//! let mut issues = Vec::new();
+//! for msg in msgs {
+//!     let msg = msg.unwrap();
+//!     if let escargot::format::Message::CompilerMessage(msg) = msg.decode().unwrap() {
+//!         if let Some(command) = annotator.github(&msg.message) {
+//!             println!("{command}");
+//!         }
+//!         issues.extend(annotator.gitlab(&msg.message));
+//!     }
+//! }
+//! let report = serde_json::to_string(&issues).unwrap();
+//! # assert_eq!(issues.len(), 2);
+//! ```
+
+use std::path;
+
+use crate::baseline;
+use crate::format::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+
+/// Convert diagnostics to annotations, with paths relative to the workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotator {
+    root: path::PathBuf,
+}
+
+impl Annotator {
+    /// Relativize paths against `workspace_root`.
+    pub fn new(workspace_root: impl AsRef<path::Path>) -> Self {
+        Self {
+            root: workspace_root.as_ref().to_owned(),
+        }
+    }
+
+    /// Render `diagnostic` as a GitHub Actions workflow command, like
+    /// `::warning file=src/lib.rs,line=3,col=1::message`.
+    ///
+    /// Returns `None` for levels without a matching command.
+    pub fn github(&self, diagnostic: &Diagnostic<'_>) -> Option<String> {
+        let command = match diagnostic.level {
+            DiagnosticLevel::Ice | DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+            DiagnosticLevel::Note | DiagnosticLevel::Help => "notice",
+            #[cfg(not(feature = "strict_unstable"))]
+            _ => return None,
+        };
+
+        let mut properties = Vec::new();
+        if let Some(span) = diagnostic.primary_span() {
+            properties.push(format!("file={}", github_property(&self.relative(span))));
+            properties.push(format!("line={}", span.line_start));
+            properties.push(format!("endLine={}", span.line_end));
+            properties.push(format!("col={}", span.column_start));
+            properties.push(format!("endColumn={}", span.column_end));
+        }
+        if let Some(code) = &diagnostic.code {
+            properties.push(format!("title={}", github_property(&code.code)));
+        }
+
+        let message = diagnostic
+            .rendered
+            .as_deref()
+            .unwrap_or(diagnostic.message.as_ref());
+        let message = github_data(message.trim_end());
+        if properties.is_empty() {
+            Some(format!("::{command}::{message}"))
+        } else {
+            Some(format!("::{command} {}::{message}", properties.join(",")))
+        }
+    }
+
+    /// Convert `diagnostic` to a GitLab Code Quality issue.
+    ///
+    /// Returns `None` for diagnostics without a location, or levels without a matching severity.
+    /// Serialize a list of issues as the report.  The fingerprint is shared with
+    /// [`Baseline`][crate::Baseline], so it is stable when lines shift.
+    pub fn gitlab(&self, diagnostic: &Diagnostic<'_>) -> Option<CodeQualityIssue> {
+        let severity = match diagnostic.level {
+            DiagnosticLevel::Ice => "blocker",
+            DiagnosticLevel::Error => "critical",
+            DiagnosticLevel::Warning => "minor",
+            DiagnosticLevel::Note | DiagnosticLevel::Help => "info",
+            #[cfg(not(feature = "strict_unstable"))]
+            _ => return None,
+        };
+        let span = diagnostic.primary_span()?;
+        Some(CodeQualityIssue {
+            description: diagnostic.message.as_ref().to_owned(),
+            check_name: diagnostic
+                .code
+                .as_ref()
+                .map(|c| c.code.as_ref().to_owned())
+                .unwrap_or_else(|| "rustc".to_owned()),
+            fingerprint: baseline::fingerprint(diagnostic),
+            severity: severity.to_owned(),
+            location: CodeQualityLocation {
+                path: self.relative(span),
+                lines: CodeQualityLines {
+                    begin: span.line_start,
+                    end: span.line_end,
+                },
+            },
+        })
+    }
+
+    /// `span`'s file relative to the workspace root, with `/` separators
+    fn relative(&self, span: &DiagnosticSpan<'_>) -> String {
+        let file = span
+            .file_name
+            .strip_prefix(&self.root)
+            .unwrap_or(&span.file_name);
+        file.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// An issue in a GitLab Code Quality report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CodeQualityIssue {
+    /// The diagnostic's message
+    pub description: String,
+    /// The lint or error code, or `rustc` without one
+    pub check_name: String,
+    /// Identifies the issue across runs
+    pub fingerprint: String,
+    /// One of `info`, `minor`, `major`, `critical`, or `blocker`
+    pub severity: String,
+    /// Where the issue is
+    pub location: CodeQualityLocation,
+}
+
+/// The location of a [`CodeQualityIssue`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CodeQualityLocation {
+    /// File relative to the workspace root
+    pub path: String,
+    /// Lines within the file
+    pub lines: CodeQualityLines,
+}
+
+/// The lines of a [`CodeQualityLocation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CodeQualityLines {
+    /// First line, 1-based
+    pub begin: usize,
+    /// Last line, 1-based
+    pub end: usize,
+}
+
+fn github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn github_property(value: &str) -> String {
+    github_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARNING: &str = r#"{"rendered":"warning: unused variable: `a`\n --> /work/src/main.rs:2:9\n","$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `a`","spans":[{"file_name":"/work/src/main.rs","byte_start":0,"byte_end":1,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}]}"#;
+
+    #[test]
+    fn github() {
+        let diagnostic: Diagnostic<'_> = serde_json::from_str(WARNING).unwrap();
+        let annotator = Annotator::new("/work");
+        assert_eq!(
+            annotator.github(&diagnostic).unwrap(),
+            "::warning file=src/main.rs,line=2,endLine=2,col=9,endColumn=10,title=unused_variables::warning: unused variable: `a`%0A --> /work/src/main.rs:2:9"
+        );
+    }
+
+    #[test]
+    fn gitlab() {
+        let diagnostic: Diagnostic<'_> = serde_json::from_str(WARNING).unwrap();
+        let issue = Annotator::new("/work").gitlab(&diagnostic).unwrap();
+        assert_eq!(issue.check_name, "unused_variables");
+        assert_eq!(issue.severity, "minor");
+        assert_eq!(issue.location.path, "src/main.rs");
+        assert_eq!(issue.location.lines.begin, 2);
+        assert_eq!(issue.fingerprint, baseline::fingerprint(&diagnostic));
+    }
+}
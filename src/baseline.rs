@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path;
+
+use crate::error::{CargoError, CargoResult, ErrorKind};
+use crate::format;
+use crate::format::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+
+/// Known diagnostics, for only failing on new ones.
+///
+/// Diagnostics are identified by their code, message, file, and the source text they point
+/// to, so they are still recognized when lines are added or removed above them.
+///
+/// # Example
+///
+/// ```rust
+/// # let target_dir = tempfile::TempDir::new().unwrap();
+/// # let baseline_path = target_dir.path().join("baseline.json");
+/// let mut current = escargot::Baseline::new();
+/// let msgs = escargot::CargoBuild::new()
+///     .manifest_path("tests/testsuite/fixtures/warn/Cargo.toml")
+///     .target_dir(target_dir.path())
+///     .exec()
+///     .unwrap();
+/// for msg in msgs {
+///     let msg = msg.unwrap();
+///     current.add(&msg.decode().unwrap());
+/// }
+///
+/// let baseline = escargot::Baseline::load(&baseline_path).unwrap_or_default();
+/// let diff = baseline.compare(&current);
+/// println!("{diff}");
+/// current.save(&baseline_path).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    diagnostics: BTreeMap<String, BaselineEntry>,
+}
+
+impl Baseline {
+    /// Create an empty baseline.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Read a baseline written by [`Baseline::save`].
+    pub fn load(path: impl AsRef<path::Path>) -> CargoResult<Self> {
+        let path = path.as_ref();
+        let raw = fs::read(path).map_err(|e| {
+            CargoError::new(ErrorKind::InvalidOutput)
+                .set_context(format!("Failed to read {}", path.display()))
+                .set_cause(e)
+        })?;
+        serde_json::from_slice(&raw).map_err(|e| {
+            CargoError::new(ErrorKind::InvalidOutput)
+                .set_context(format!("Invalid baseline {}", path.display()))
+                .set_cause(e)
+        })
+    }
+
+    /// Write the baseline to `path`.
+    pub fn save(&self, path: impl AsRef<path::Path>) -> CargoResult<()> {
+        let path = path.as_ref();
+        let mut raw = serde_json::to_vec_pretty(self)
+            .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
+        raw.push(b'\n');
+        fs::write(path, raw).map_err(|e| {
+            CargoError::new(ErrorKind::InvalidOutput)
+                .set_context(format!("Failed to write {}", path.display()))
+                .set_cause(e)
+        })
+    }
+
+    /// Record `msg` if it is a warning or error.
+    ///
+    /// Returns whether the message was recorded.
+    pub fn add(&mut self, msg: &format::Message<'_>) -> bool {
+        match msg {
+            format::Message::CompilerMessage(message) => self.add_diagnostic(&message.message),
+            _ => false,
+        }
+    }
+
+    /// Record `diagnostic` if it is a warning or error.
+    ///
+    /// Returns whether the diagnostic was recorded.
+    pub fn add_diagnostic(&mut self, diagnostic: &Diagnostic<'_>) -> bool {
+        match diagnostic.level {
+            DiagnosticLevel::Ice | DiagnosticLevel::Error | DiagnosticLevel::Warning => {}
+            _ => return false,
+        }
+        let entry = BaselineEntry::new(diagnostic);
+        self.diagnostics
+            .entry(entry.fingerprint.clone())
+            .and_modify(|e| e.count += 1)
+            .or_insert(entry);
+        true
+    }
+
+    /// Every diagnostic recorded, by fingerprint
+    pub fn diagnostics(&self) -> impl Iterator<Item = &BaselineEntry> {
+        self.diagnostics.values()
+    }
+
+    /// Diagnostics in `current` but not in this baseline, and those no longer present.
+    pub fn compare(&self, current: &Baseline) -> BaselineDiff {
+        fn missing(from: &Baseline, to: &Baseline) -> Vec<BaselineEntry> {
+            from.diagnostics
+                .values()
+                .filter_map(|entry| {
+                    let known = to
+                        .diagnostics
+                        .get(&entry.fingerprint)
+                        .map(|e| e.count)
+                        .unwrap_or(0);
+                    (known < entry.count).then(|| BaselineEntry {
+                        count: entry.count - known,
+                        ..entry.clone()
+                    })
+                })
+                .collect()
+        }
+
+        BaselineDiff {
+            new: missing(current, self),
+            fixed: missing(self, current),
+        }
+    }
+}
+
+/// A diagnostic in a [`Baseline`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BaselineEntry {
+    /// Identifies the diagnostic across runs
+    pub fingerprint: String,
+    /// The lint or error code, like `unused_variables` or `E0308`
+    pub code: Option<String>,
+    /// The diagnostic's message
+    pub message: String,
+    /// File of the primary span, with `/` separators
+    pub file: Option<String>,
+    /// How many times the diagnostic was reported
+    pub count: usize,
+}
+
+impl BaselineEntry {
+    fn new(diagnostic: &Diagnostic<'_>) -> Self {
+        let code = diagnostic.code.as_ref().map(|c| c.code.as_ref().to_owned());
+        let file = diagnostic
+            .primary_span()
+            .map(|s| s.file_name.to_string_lossy().replace('\\', "/"));
+        Self {
+            fingerprint: fingerprint(diagnostic),
+            code,
+            message: diagnostic.message.as_ref().to_owned(),
+            file,
+            count: 1,
+        }
+    }
+}
+
+impl fmt::Display for BaselineEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}: ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(code) = &self.code {
+            write!(f, " [{code}]")?;
+        }
+        if 1 < self.count {
+            write!(f, " (x{})", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Differences between a [`Baseline`] and the current diagnostics.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BaselineDiff {
+    /// Diagnostics not in the baseline
+    pub new: Vec<BaselineEntry>,
+    /// Baseline diagnostics that are no longer reported
+    pub fixed: Vec<BaselineEntry>,
+}
+
+impl BaselineDiff {
+    /// Whether no new diagnostics were reported
+    pub fn is_clean(&self) -> bool {
+        self.new.is_empty()
+    }
+}
+
+impl fmt::Display for BaselineDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.new {
+            writeln!(f, "new: {entry}")?;
+        }
+        for entry in &self.fixed {
+            writeln!(f, "fixed: {entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Identify `diagnostic` by its code, message, file, and highlighted source.
+pub(crate) fn fingerprint(diagnostic: &Diagnostic<'_>) -> String {
+    let code = diagnostic.code.as_ref().map(|c| c.code.as_ref());
+    let span = diagnostic.primary_span();
+    let file = span.map(|s| s.file_name.to_string_lossy().replace('\\', "/"));
+    let text = span.map(span_text).unwrap_or_default();
+
+    let mut hasher = Fnv1a::default();
+    for part in [
+        code.unwrap_or_default(),
+        diagnostic.message.as_ref(),
+        file.as_deref().unwrap_or_default(),
+        text.as_str(),
+    ] {
+        hasher.write(part.as_bytes());
+        hasher.write(&[0]);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// The highlighted source, with whitespace collapsed so reformatting doesn't matter.
+fn span_text(span: &DiagnosticSpan<'_>) -> String {
+    let mut text = Vec::new();
+    for line in &span.text {
+        let start = line.highlight_start.saturating_sub(1);
+        let len = line.highlight_end.saturating_sub(line.highlight_start);
+        let highlighted: String = line.text.chars().skip(start).take(len).collect();
+        text.extend(highlighted.split_whitespace().map(|s| s.to_owned()));
+    }
+    text.join(" ")
+}
+
+/// A hash that is stable across Rust versions, unlike [`std::hash::DefaultHasher`].
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(raws: &[&str]) -> Baseline {
+        let mut baseline = Baseline::new();
+        for raw in raws {
+            let diagnostic: Diagnostic<'_> = serde_json::from_str(raw).unwrap();
+            assert!(baseline.add_diagnostic(&diagnostic));
+        }
+        baseline
+    }
+
+    #[test]
+    fn line_shifts() {
+        let old = baseline(&[
+            r#"{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let a = 1;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+            r#"{"message":"unused variable: `b`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":35,"byte_end":36,"line_start":3,"line_end":3,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let b = 2;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+        ]);
+        // `a` moved down while `b` was replaced by `c`
+        let current = baseline(&[
+            r#"{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":160,"byte_end":161,"line_start":12,"line_end":12,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let a = 1;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+            r#"{"message":"unused variable: `c`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":190,"byte_end":191,"line_start":14,"line_end":14,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let c = 3;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+        ]);
+        let diff = old.compare(&current);
+        let new: Vec<_> = diff.new.iter().map(|e| e.message.as_str()).collect();
+        let fixed: Vec<_> = diff.fixed.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(new, ["unused variable: `c`"]);
+        assert_eq!(fixed, ["unused variable: `b`"]);
+        assert!(!diff.is_clean());
+    }
+
+    #[test]
+    fn duplicates() {
+        let old = baseline(&[
+            r#"{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let a = 1;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+        ]);
+        // A second `let a = 1;` with the same fingerprint
+        let current = baseline(&[
+            r#"{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let a = 1;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+            r#"{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":90,"byte_end":91,"line_start":7,"line_end":7,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let a = 1;","highlight_start":9,"highlight_end":10}]}],"children":[]}"#,
+        ]);
+        let diff = old.compare(&current);
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.new[0].count, 1);
+        assert!(diff.fixed.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "new: src/main.rs: unused variable: `a` [unused_variables]\n"
+        );
+    }
+}
@@ -1,11 +1,25 @@
-use std::ffi::OsStr;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path;
 use std::process;
 
+use crate::cache;
+use crate::cargo::CURRENT_PROFILE;
 use crate::cargo::CURRENT_TARGET;
 use crate::cargo::Cargo;
-use crate::error::CargoResult;
+use crate::config::ConfigValue;
+use crate::error::{CargoError, CargoResult, ErrorKind};
+#[cfg(feature = "cargo_unstable")]
+use crate::format;
+use crate::fresh;
+use crate::isolation;
 use crate::msg::CommandMessages;
+use crate::profile;
+use crate::report::{Reporter, ReporterSlot};
 use crate::run::CargoRun;
+use crate::shell;
 #[cfg(feature = "test_unstable")]
 use crate::test::CargoTest;
 
@@ -24,11 +38,40 @@ use crate::test::CargoTest;
 ///     .exec()
 ///     .unwrap();
 /// ```
-#[derive(Debug)]
+///
+/// The configuration is only turned into a [`process::Command`] when run, so a base
+/// configuration can be cloned to derive variants.  It can also be serialized, failing if any
+/// argument is not UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CargoBuild {
-    cmd: process::Command,
-    bin: bool,
-    example: bool,
+    program: OsArg,
+    base_args: Vec<OsArg>,
+    current_dir: Option<path::PathBuf>,
+    envs: BTreeMap<OsArg, Option<OsArg>>,
+    isolated: bool,
+    cargo_home: Option<path::PathBuf>,
+    packages: Vec<OsArg>,
+    workspace: bool,
+    exclude: Vec<OsArg>,
+    selection: Selection,
+    manifest_path: Option<path::PathBuf>,
+    profile: Option<String>,
+    targets: Vec<OsArg>,
+    target_dir: Option<path::PathBuf>,
+    configs: Vec<OsArg>,
+    locked: bool,
+    frozen: bool,
+    offline: bool,
+    all_features: bool,
+    no_default_features: bool,
+    features: Vec<OsArg>,
+    timings: bool,
+    timings_json: bool,
+    args: Vec<OsArg>,
+    skip_if_fresh: bool,
+    #[serde(skip)]
+    reporter: ReporterSlot,
 }
 
 impl CargoBuild {
@@ -54,15 +97,181 @@ impl CargoBuild {
 
     pub(crate) fn with_command(cmd: process::Command) -> Self {
         Self {
-            cmd,
-            bin: false,
-            example: false,
+            program: OsArg::new(cmd.get_program()),
+            base_args: cmd.get_args().map(OsArg::new).collect(),
+            current_dir: cmd.get_current_dir().map(|d| d.to_owned()),
+            envs: cmd
+                .get_envs()
+                .map(|(k, v)| (OsArg::new(k), v.map(OsArg::new)))
+                .collect(),
+            isolated: false,
+            cargo_home: None,
+            packages: Vec::new(),
+            workspace: false,
+            exclude: Vec::new(),
+            selection: Selection::default(),
+            manifest_path: None,
+            profile: None,
+            targets: Vec::new(),
+            target_dir: None,
+            configs: Vec::new(),
+            locked: false,
+            frozen: false,
+            offline: false,
+            all_features: false,
+            no_default_features: false,
+            features: Vec::new(),
+            timings: false,
+            timings_json: false,
+            args: Vec::new(),
+            skip_if_fresh: false,
+            reporter: ReporterSlot::default(),
         }
     }
 
     /// Return the underlying [`process::Command`]
+    ///
+    /// Like building, this creates the [`Self::cargo_home`] the command relies on.
     pub fn into_command(self) -> process::Command {
-        self.cmd
+        if let Some(home) = &self.cargo_home {
+            // Without the shared registry, cargo reports the missing dependencies when run
+            if let Err(err) = isolation::prepare_home(home) {
+                log::warn!("{err}");
+            }
+        }
+        self.to_command()
+    }
+
+    /// Lower to a [`process::Command`], preparing anything it relies on.
+    fn spawn_command(&self) -> CargoResult<process::Command> {
+        self.check_profile()?;
+        if let Some(home) = &self.cargo_home {
+            isolation::prepare_home(home)?;
+        }
+        let cmd = self.to_command();
+        if self.isolated {
+            log::debug!("Isolated from {:?}", self.stripped_env());
+        }
+        Ok(cmd)
+    }
+
+    /// Spawn cargo, reporting its messages to `reporter`.
+    fn messages(&self, reporter: ReporterSlot) -> CargoResult<CommandMessages> {
+        #[cfg(feature = "tracing")]
+        let span = {
+            let join = |args: &[OsArg]| {
+                let args: Vec<_> = args.iter().map(|a| a.0.to_string_lossy()).collect();
+                args.join(",")
+            };
+            tracing::info_span!(
+                "cargo",
+                command = %shell::redacted_command_line(&self.to_command()),
+                package = %join(&self.packages),
+                target = %join(&self.targets),
+            )
+        };
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let msgs = CommandMessages::with_command(self.spawn_command()?)?.with_reporter(reporter);
+        #[cfg(feature = "tracing")]
+        let msgs = msgs.with_span(span.clone());
+        Ok(msgs)
+    }
+
+    fn to_command(&self) -> process::Command {
+        let mut cmd = process::Command::new(&self.program.0);
+        cmd.args(&self.base_args);
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+        for key in self.stripped_env() {
+            cmd.env_remove(key);
+        }
+        if let Some(home) = &self.cargo_home {
+            cmd.env("CARGO_HOME", home);
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+
+        for package in &self.packages {
+            cmd.arg("--package").arg(package);
+        }
+        if self.workspace {
+            cmd.arg("--workspace");
+        }
+        for package in &self.exclude {
+            cmd.arg("--exclude").arg(package);
+        }
+        self.selection.lower(&mut cmd);
+        if let Some(path) = &self.manifest_path {
+            cmd.arg("--manifest-path").arg(path);
+        }
+        match self.profile.as_deref() {
+            Some("release") => {
+                cmd.arg("--release");
+            }
+            Some(profile) => {
+                cmd.arg("--profile").arg(profile);
+            }
+            None => {}
+        }
+        for target in &self.targets {
+            cmd.arg("--target").arg(target);
+        }
+        if let Some(dir) = &self.target_dir {
+            cmd.arg("--target-dir").arg(dir);
+        }
+        for config in &self.configs {
+            cmd.arg("--config").arg(config);
+        }
+        if self.locked {
+            cmd.arg("--locked");
+        }
+        if self.frozen {
+            cmd.arg("--frozen");
+        }
+        if self.offline || (!self.frozen && self.shares_registry()) {
+            cmd.arg("--offline");
+        }
+        if self.timings_json {
+            cmd.arg("-Zunstable-options").arg("--timings=json");
+        } else if self.timings {
+            cmd.arg("--timings");
+        }
+        if self.all_features {
+            cmd.arg("--all-features");
+        }
+        if self.no_default_features {
+            cmd.arg("--no-default-features");
+        }
+        for features in &self.features {
+            cmd.arg("--features").arg(features);
+        }
+        cmd.args(&self.args);
+        cmd
+    }
+
+    /// Render the command as a POSIX shell command line, for reproducing the build.
+    ///
+    /// This includes environment variables changed via [`Self::env`] and [`Self::env_remove`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let line = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .env("RUSTFLAGS", "-D warnings")
+    ///     .to_command_line();
+    /// assert!(line.starts_with("RUSTFLAGS='-D warnings' "));
+    /// assert!(line.ends_with(" build --message-format=json --bin bin"));
+    /// ```
+    pub fn to_command_line(&self) -> String {
+        shell::command_line(&self.to_command())
     }
 
     /// Build from `name` package in workspaces.
@@ -79,8 +288,45 @@ impl CargoBuild {
     ///     .exec()
     ///     .unwrap();
     /// ```
-    pub fn package<S: AsRef<OsStr>>(self, name: S) -> Self {
-        self.arg("--package").arg(name)
+    pub fn package<S: AsRef<OsStr>>(mut self, name: S) -> Self {
+        self.packages.push(OsArg::new(name.as_ref()));
+        self
+    }
+
+    /// Build all packages in the workspace.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// escargot::CargoBuild::new()
+    ///     .workspace()
+    ///     .manifest_path("tests/testsuite/fixtures/bin_lib/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .exec()
+    ///     .unwrap();
+    /// ```
+    pub fn workspace(mut self) -> Self {
+        self.workspace = true;
+        self
+    }
+
+    /// Exclude `name` package from [`Self::workspace`].
+    pub fn exclude<S: AsRef<OsStr>>(mut self, name: S) -> Self {
+        self.exclude.push(OsArg::new(name.as_ref()));
+        self
+    }
+
+    /// Build only the library.
+    pub fn lib(mut self) -> Self {
+        self.selection.lib = true;
+        self
+    }
+
+    /// Build all targets, including tests, benches, and examples.
+    pub fn all_targets(mut self) -> Self {
+        self.selection.all_targets = true;
+        self
     }
 
     /// Build all binaries.
@@ -97,12 +343,14 @@ impl CargoBuild {
     ///     .unwrap();
     /// ```
     pub fn bins(mut self) -> Self {
-        self.bin = true;
-        self.arg("--bins")
+        self.selection.all_bins = true;
+        self
     }
 
     /// Build only `name` binary.
     ///
+    /// `name` may be a glob pattern, like `bin*`.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -115,8 +363,8 @@ impl CargoBuild {
     ///     .unwrap();
     /// ```
     pub fn bin<S: AsRef<OsStr>>(mut self, name: S) -> Self {
-        self.bin = true;
-        self.arg("--bin").arg(name)
+        self.selection.bins.push(OsArg::new(name.as_ref()));
+        self
     }
 
     /// Build all examples
@@ -133,12 +381,14 @@ impl CargoBuild {
     ///     .unwrap();
     /// ```
     pub fn examples(mut self) -> Self {
-        self.example = true;
-        self.arg("--examples")
+        self.selection.all_examples = true;
+        self
     }
 
     /// Build only `name` example.
     ///
+    /// `name` may be a glob pattern, like `example_*`.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -151,8 +401,8 @@ impl CargoBuild {
     ///     .unwrap();
     /// ```
     pub fn example<S: AsRef<OsStr>>(mut self, name: S) -> Self {
-        self.example = true;
-        self.arg("--example").arg(name)
+        self.selection.examples.push(OsArg::new(name.as_ref()));
+        self
     }
 
     /// Build all tests
@@ -168,12 +418,15 @@ impl CargoBuild {
     ///     .exec()
     ///     .unwrap();
     /// ```
-    pub fn tests(self) -> Self {
-        self.arg("--tests")
+    pub fn tests(mut self) -> Self {
+        self.selection.all_tests = true;
+        self
     }
 
     /// Build only `name` test.
     ///
+    /// `name` may be a glob pattern, like `test*`.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -185,18 +438,62 @@ impl CargoBuild {
     ///     .exec()
     ///     .unwrap();
     /// ```
-    pub fn test<S: AsRef<OsStr>>(self, name: S) -> Self {
-        self.arg("--test").arg(name)
+    pub fn test<S: AsRef<OsStr>>(mut self, name: S) -> Self {
+        self.selection.tests.push(OsArg::new(name.as_ref()));
+        self
+    }
+
+    /// Build all benches
+    pub fn benches(mut self) -> Self {
+        self.selection.all_benches = true;
+        self
+    }
+
+    /// Build only `name` bench.
+    ///
+    /// `name` may be a glob pattern, like `bench*`.
+    pub fn bench<S: AsRef<OsStr>>(mut self, name: S) -> Self {
+        self.selection.benches.push(OsArg::new(name.as_ref()));
+        self
     }
 
     /// Path to Cargo.toml
-    pub fn manifest_path<S: AsRef<OsStr>>(self, path: S) -> Self {
-        self.arg("--manifest-path").arg(path)
+    pub fn manifest_path<S: AsRef<OsStr>>(mut self, path: S) -> Self {
+        self.manifest_path = Some(path::PathBuf::from(path.as_ref()));
+        self
     }
 
     /// Build artifacts in release mode, with optimizations.
-    pub fn release(self) -> Self {
-        self.arg("--release")
+    pub fn release(mut self) -> Self {
+        self.profile = Some("release".to_owned());
+        self
+    }
+
+    /// Build artifacts with the specified profile.
+    ///
+    /// Before running cargo, `name` is checked against the built-in profiles, the `[profile]`
+    /// tables of the manifest and its ancestors, and cargo config, including [`Self::config`] and
+    /// `CARGO_PROFILE_*` variables.  Undefined profiles fail with
+    /// [`ErrorKind::UndefinedProfile`].  The profile used is reported by
+    /// [`CargoRun::profile_name`] and its settings by [`CargoRun::profile`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// let run = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .profile("release")
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .run()
+    ///     .unwrap();
+    /// assert_eq!(run.profile_name(), "release");
+    /// assert!(!run.profile().debug_assertions);
+    /// ```
+    pub fn profile<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.profile = Some(name.as_ref().to_owned());
+        self
     }
 
     /// Inserts or updates an environment variable mapping.
@@ -205,8 +502,8 @@ impl CargoBuild {
         K: AsRef<OsStr>,
         V: AsRef<OsStr>,
     {
-        self.cmd.env(key, val);
-
+        self.envs
+            .insert(OsArg::new(key.as_ref()), Some(OsArg::new(val.as_ref())));
         self
     }
 
@@ -215,11 +512,14 @@ impl CargoBuild {
     where
         K: AsRef<OsStr>,
     {
-        self.cmd.env_remove(key);
+        self.envs.insert(OsArg::new(key.as_ref()), None);
         self
     }
 
     /// Infer [`Self::release`] from how the current process was built
+    ///
+    /// This guesses from `debug_assertions`, see [`Self::current_profile`] to reproduce custom
+    /// profiles.
     pub fn current_release(self) -> Self {
         #[cfg(debug_assertions)]
         {
@@ -231,9 +531,31 @@ impl CargoBuild {
         }
     }
 
+    /// Infer the profile from how the current process was built
+    ///
+    /// See [`CURRENT_PROFILE`][crate::CURRENT_PROFILE].  Custom profiles must also be defined for
+    /// the package being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .current_profile()
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .exec()
+    ///     .unwrap();
+    /// ```
+    pub fn current_profile(self) -> Self {
+        self.profile(CURRENT_PROFILE)
+    }
+
     /// Build for the target triplet.
-    pub fn target<S: AsRef<OsStr>>(self, triplet: S) -> Self {
-        self.arg("--target").arg(triplet)
+    pub fn target<S: AsRef<OsStr>>(mut self, triplet: S) -> Self {
+        self.targets.push(OsArg::new(triplet.as_ref()));
+        self
     }
 
     /// Infer [`Self::target`] from how the current process was built
@@ -242,44 +564,219 @@ impl CargoBuild {
     }
 
     /// Directory for all generated artifacts
-    pub fn target_dir<S: AsRef<OsStr>>(self, dir: S) -> Self {
-        self.arg("--target-dir").arg(dir)
+    pub fn target_dir<S: AsRef<OsStr>>(mut self, dir: S) -> Self {
+        self.target_dir = Some(path::PathBuf::from(dir.as_ref()));
+        self
+    }
+
+    /// Ignore environment variables that influence cargo and rustc.
+    ///
+    /// Unless overridden by [`Self::env`], this removes:
+    /// - `CARGO_HOME`, `CARGO_INCREMENTAL`, `CARGO_CACHE_RUSTC_INFO`, `CARGO_LOG`
+    /// - `RUSTC`, `RUSTC_WRAPPER`, `RUSTC_WORKSPACE_WRAPPER`, `RUSTC_BOOTSTRAP`, `RUSTDOC`
+    /// - `RUSTFLAGS`, `RUSTDOCFLAGS`, `CARGO_ENCODED_RUSTFLAGS`, `CARGO_ENCODED_RUSTDOCFLAGS`
+    /// - `CARGO_ALIAS_*`, `CARGO_BUILD_*`, `CARGO_HTTP_*`, `CARGO_NET_*`, `CARGO_PROFILE_*`,
+    ///   `CARGO_REGISTRIES_*`, `CARGO_REGISTRY_*`, `CARGO_TARGET_*`, `CARGO_TERM_*`,
+    ///   `CARGO_UNSTABLE_*`
+    ///
+    /// `RUSTUP_*` variables are kept so the toolchain doesn't change.  See
+    /// [`Self::stripped_env`] for what is removed from the current process.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// let build = escargot::CargoBuild::new()
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .isolated();
+    /// for key in build.stripped_env() {
+    ///     println!("stripped={}", key.to_string_lossy());
+    /// }
+    /// build.exec().unwrap();
+    /// ```
+    pub fn isolated(mut self) -> Self {
+        self.isolated = true;
+        self
+    }
+
+    /// Environment variables of the current process removed by [`Self::isolated`]
+    pub fn stripped_env(&self) -> Vec<OsString> {
+        if !self.isolated {
+            return Vec::new();
+        }
+        let mut keys: Vec<_> = env::vars_os()
+            .map(|(key, _)| key)
+            .filter(|key| isolation::is_stripped(key) && !self.envs.contains_key(key.as_os_str()))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Use `dir` as `CARGO_HOME`, ignoring the user's cargo config and credentials.
+    ///
+    /// `dir` is created when building or by [`Self::into_command`].  The crates and index already
+    /// downloaded to the current `CARGO_HOME` are shared: the build is run `--offline` so cargo
+    /// doesn't download crates or fetch the index, and crates are unpacked into `dir`.  This isn't
+    /// read-only as cargo may still write cache files into the shared index.  Dependencies that
+    /// weren't downloaded before fail to resolve.
+    pub fn cargo_home<S: AsRef<path::Path>>(mut self, dir: S) -> Self {
+        self.cargo_home = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Whether [`Self::cargo_home`] shares the current `CARGO_HOME`'s registry
+    fn shares_registry(&self) -> bool {
+        self.cargo_home
+            .as_deref()
+            .is_some_and(|home| isolation::shared_registry(home).is_some())
+    }
+
+    /// Run cargo from `dir`.
+    ///
+    /// Relative paths, like [`Self::manifest_path`], are resolved against `dir`, as is the
+    /// discovery of `.cargo/config.toml` files.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// escargot::CargoBuild::new()
+    ///     .current_dir("tests/testsuite/fixtures/bin")
+    ///     .target_dir(target_dir.path())
+    ///     .exec()
+    ///     .unwrap();
+    /// ```
+    pub fn current_dir<S: AsRef<path::Path>>(mut self, dir: S) -> Self {
+        self.current_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Override a config value, like `--config KEY=VALUE`.
+    ///
+    /// `key` is a dotted TOML key while `value` is quoted as TOML.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// escargot::CargoBuild::new()
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .config("build.target-dir", target_dir.path())
+    ///     .config("profile.dev.debug", false)
+    ///     .config("build.rustflags", ["-C", "debug-assertions"])
+    ///     .exec()
+    ///     .unwrap();
+    /// ```
+    pub fn config<K: AsRef<str>, V: Into<ConfigValue>>(mut self, key: K, value: V) -> Self {
+        let config = format!("{}={}", key.as_ref(), value.into());
+        self.configs.push(OsArg(config.into()));
+        self
+    }
+
+    /// Load additional config from a TOML file, like `--config path.toml`.
+    pub fn config_file<S: AsRef<path::Path>>(mut self, path: S) -> Self {
+        self.configs.push(OsArg::new(path.as_ref().as_os_str()));
+        self
+    }
+
+    /// Require `Cargo.lock` to be up-to-date
+    pub fn locked(mut self) -> Self {
+        self.locked = true;
+        self
+    }
+
+    /// Require `Cargo.lock` and the dependency cache to be up-to-date
+    pub fn frozen(mut self) -> Self {
+        self.frozen = true;
+        self
+    }
+
+    /// Run without accessing the network
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Report how long each unit took to compile.
+    ///
+    /// This only writes an HTML report to `cargo-timings` in the target directory, without any
+    /// [`Message::TimingInfo`][crate::format::Message::TimingInfo].  For messages to aggregate
+    /// with [`TimingReport`][crate::TimingReport], use `timings_json` (requires the
+    /// `cargo_unstable` feature).
+    pub fn timings(mut self) -> Self {
+        self.timings = true;
+        self
+    }
+
+    /// Report how long each unit took to build as
+    /// [`Message::TimingInfo`][crate::format::Message::TimingInfo] messages, via
+    /// `-Zunstable-options --timings=json`.
+    ///
+    /// Aggregate the messages with [`TimingReport`][crate::TimingReport].  This replaces
+    /// [`Self::timings`].
+    ///
+    /// Required feature: `cargo_unstable` since this requires a nightly cargo.
+    #[cfg(feature = "cargo_unstable")]
+    pub fn timings_json(mut self) -> Self {
+        self.timings_json = true;
+        self
     }
 
     /// Activate all available features
-    pub fn all_features(self) -> Self {
-        self.arg("--all-features")
+    pub fn all_features(mut self) -> Self {
+        self.all_features = true;
+        self
     }
 
     /// Do not activate the `default` feature
-    pub fn no_default_features(self) -> Self {
-        self.arg("--no-default-features")
+    pub fn no_default_features(mut self) -> Self {
+        self.no_default_features = true;
+        self
     }
 
     /// Space-separated list of features to activate
-    pub fn features<S: AsRef<OsStr>>(self, features: S) -> Self {
-        self.arg("--features").arg(features)
+    pub fn features<S: AsRef<OsStr>>(mut self, features: S) -> Self {
+        self.features.push(OsArg::new(features.as_ref()));
+        self
     }
 
     /// Manually pass an argument that is unsupported.
     ///
+    /// Raw arguments are passed after all typed options, like [`Self::bin`] or
+    /// [`Self::features`], regardless of the order they were called in.
+    ///
     /// Caution: Passing in `--` can throw off the API.
     pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Self {
-        self.cmd.arg(arg);
+        self.args.push(OsArg::new(arg.as_ref()));
         self
     }
 
     /// Manually pass arguments that are unsupported.
     ///
+    /// Like [`Self::arg`], these are passed after all typed options.
+    ///
     /// Caution: Passing in `--` can throw off the API.
     pub fn args<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(mut self, args: I) -> Self {
-        self.cmd.args(args);
+        self.args
+            .extend(args.into_iter().map(|a| OsArg::new(a.as_ref())));
+        self
+    }
+
+    /// Observe progress of the build.
+    ///
+    /// The reporter is called as messages are read from [`Self::exec`] or while
+    /// [`Self::run`] and `run_tests` look for their artifacts.  It isn't part of the
+    /// configuration, so it is ignored when comparing, hashing, or serializing.
+    pub fn reporter(mut self, reporter: std::sync::Arc<dyn Reporter>) -> Self {
+        self.reporter = ReporterSlot::new(reporter);
         self
     }
 
     /// Build the configured target, returning compiler messages.
     pub fn exec(self) -> CargoResult<CommandMessages> {
-        CommandMessages::with_command(self.cmd)
+        let reporter = self.reporter.clone();
+        self.messages(reporter)
     }
 
     /// Provide a proxy for running the built target.
@@ -299,8 +796,200 @@ impl CargoBuild {
     /// println!("artifact={}", run.path().display());
     /// ```
     pub fn run(self) -> CargoResult<CargoRun> {
-        let msgs = CommandMessages::with_command(self.cmd)?;
-        CargoRun::from_message(msgs, self.bin, self.example)
+        let kind = self.selection.runnable_kind()?;
+        let fingerprint = self.skip_if_fresh.then(|| self.fingerprint());
+        if let Some(run) = fingerprint.as_ref().and_then(|f| f.lookup()) {
+            return Ok(run);
+        }
+
+        let msgs = self.messages(self.reporter.clone().or_default())?;
+        let run = CargoRun::from_message(msgs, kind)?;
+        if let Some(fingerprint) = fingerprint {
+            fingerprint.record(&run);
+        }
+        Ok(run)
+    }
+
+    fn fingerprint(&self) -> fresh::Fingerprint {
+        let cwd = self.resolved_current_dir();
+        let package_dir = self.package_dir(&cwd);
+        let target_dir = match &self.target_dir {
+            Some(dir) => cwd.join(dir),
+            None => package_dir.join("target"),
+        };
+        let mut config = vec![self.to_command_line()];
+        // The environment cargo inherits isn't part of the command line
+        let mut inherited: Vec<_> = self
+            .inherited_env()
+            .map(|(key, value)| format!("{}={}", key.to_string_lossy(), value.to_string_lossy()))
+            .collect();
+        inherited.sort();
+        config.extend(inherited);
+        match Cargo::with_command(self.to_command()).rustc_version() {
+            Ok(version) => config.push(format!(
+                "rustc {} {}",
+                version.version,
+                version.commit_hash.unwrap_or_default()
+            )),
+            // Building reports the error
+            Err(err) => log::debug!("Not fingerprinting the toolchain: {err}"),
+        }
+        fresh::Fingerprint::new(&config.join("\n"), &package_dir, &target_dir)
+    }
+
+    /// The `CARGO*` and `RUST*` variables cargo inherits from the current process
+    fn inherited_env(&self) -> impl Iterator<Item = (OsString, OsString)> + '_ {
+        env::vars_os().filter(|(key, _)| {
+            let name = key.to_string_lossy();
+            let affects_build = name.starts_with("CARGO") || name.starts_with("RUST");
+            let overridden = self.envs.contains_key(key.as_os_str())
+                || (self.isolated && isolation::is_stripped(key));
+            affects_build && !overridden
+        })
+    }
+
+    /// Fail if [`Self::profile`] isn't defined by cargo, the manifest, or cargo config.
+    fn check_profile(&self) -> CargoResult<()> {
+        let Some(name) = self.profile.as_deref() else {
+            return Ok(());
+        };
+        if profile::is_built_in(name) {
+            return Ok(());
+        }
+
+        let cwd = self.resolved_current_dir();
+        let mut files: Vec<_> = self
+            .package_dir(&cwd)
+            .ancestors()
+            .map(|dir| dir.join("Cargo.toml"))
+            .collect();
+        files.extend(self.manifest_path.iter().map(|manifest| cwd.join(manifest)));
+        for dir in cwd.ancestors() {
+            files.push(dir.join(".cargo").join("config.toml"));
+            files.push(dir.join(".cargo").join("config"));
+        }
+        let home = match (&self.cargo_home, self.envs.get(OsStr::new("CARGO_HOME"))) {
+            (Some(home), _) => Some(home.clone()),
+            (None, Some(home)) => home.as_ref().map(|home| path::PathBuf::from(&home.0)),
+            (None, None) => isolation::ambient_home(),
+        };
+        if let Some(home) = home {
+            files.push(home.join("config.toml"));
+            files.push(home.join("config"));
+        }
+        // `--config` is either a `KEY=VALUE` TOML line or a file
+        files.extend(self.configs.iter().map(|config| cwd.join(&config.0)));
+
+        let mut env_keys = self
+            .envs
+            .iter()
+            .filter(|(_, value)| value.is_some())
+            .map(|(key, _)| key.0.clone())
+            .chain(self.inherited_env().map(|(key, _)| key));
+        let defined = self
+            .configs
+            .iter()
+            .any(|config| profile::is_defined(&config.0.to_string_lossy(), name))
+            || files
+                .iter()
+                .filter_map(|file| fs::read_to_string(file).ok())
+                .any(|toml| profile::is_defined(&toml, name))
+            || env_keys.any(|key| profile::is_defined_by_env(&key, name));
+        if defined {
+            Ok(())
+        } else {
+            Err(
+                CargoError::new(ErrorKind::UndefinedProfile).set_context(format!(
+                    "profile `{name}` is not defined in the manifest or cargo config"
+                )),
+            )
+        }
+    }
+
+    /// The absolute directory cargo will run in
+    fn resolved_current_dir(&self) -> path::PathBuf {
+        let process_dir = env::current_dir().unwrap_or_default();
+        match &self.current_dir {
+            Some(dir) => process_dir.join(dir),
+            None => process_dir,
+        }
+    }
+
+    /// The directory of the manifest cargo will use when run in `cwd`
+    fn package_dir(&self, cwd: &path::Path) -> path::PathBuf {
+        match &self.manifest_path {
+            Some(manifest) => cwd
+                .join(manifest)
+                .parent()
+                .map(|p| p.to_owned())
+                .unwrap_or_else(|| cwd.to_owned()),
+            // Like cargo, search upwards for a manifest
+            None => cwd
+                .ancestors()
+                .find(|dir| dir.join("Cargo.toml").is_file())
+                .unwrap_or(cwd)
+                .to_owned(),
+        }
+    }
+
+    /// Let [`Self::run`] skip cargo when the binary is fresh.
+    ///
+    /// After building, a fingerprint of the configuration, the package's files, and any
+    /// `Cargo.lock` or `.cargo/config.toml` above it is recorded in the target directory (or
+    /// `<package>/target` when unset).  The configuration includes the `CARGO*` and `RUST*`
+    /// environment variables cargo inherits, like `RUSTFLAGS`, and the version of `rustc`.  Later
+    /// runs with the same fingerprint reuse the binary without invoking cargo, otherwise they
+    /// build as usual.
+    ///
+    /// Changes outside of the package aren't detected, like to path dependencies elsewhere in
+    /// the workspace, to `$CARGO_HOME/config.toml`, or to files read by wrappers like
+    /// `RUSTC_WRAPPER`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// let build = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .skip_if_fresh();
+    /// let built = build.clone().run().unwrap();
+    /// let reused = build.run().unwrap();
+    /// assert_eq!(built.path(), reused.path());
+    /// ```
+    pub fn skip_if_fresh(mut self) -> Self {
+        self.skip_if_fresh = true;
+        self
+    }
+
+    /// Like [`Self::run`] but only builds once per process for each configuration.
+    ///
+    /// Concurrent calls with the same configuration wait for a single build.  Failed builds
+    /// aren't cached.  Call [`Self::invalidate_cached`] or [`crate::clear_run_cache`] after
+    /// changing the sources of the package within the process.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// let build = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .current_release()
+    ///     .current_target()
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path());
+    /// let first = build.clone().run_cached().unwrap();
+    /// let second = build.run_cached().unwrap();
+    /// assert_eq!(first.path(), second.path());
+    /// ```
+    pub fn run_cached(self) -> CargoResult<CargoRun> {
+        cache::run(self)
+    }
+
+    /// Forget the [`Self::run_cached`] result for this configuration.
+    pub fn invalidate_cached(&self) {
+        cache::invalidate(self);
     }
 
     /// Provide a proxy for running the built target.
@@ -323,9 +1012,206 @@ impl CargoBuild {
     /// ```
     #[cfg(feature = "test_unstable")]
     pub fn run_tests(self) -> CargoResult<impl Iterator<Item = CargoResult<CargoTest>>> {
-        let msgs = CommandMessages::with_command(self.cmd)?;
+        self.selection.check_tests()?;
+        let msgs = self.messages(self.reporter.clone().or_default())?;
         Ok(CargoTest::with_messages(msgs))
     }
+
+    /// Report the graph of units that would be built, without building them.
+    ///
+    /// Required feature: `cargo_unstable` since this requires a nightly cargo.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// let graph = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .unit_graph()
+    ///     .unwrap();
+    /// for unit in graph.root_units() {
+    ///     println!("root={}", unit.target.name);
+    /// }
+    /// ```
+    #[cfg(feature = "cargo_unstable")]
+    pub fn unit_graph(self) -> CargoResult<format::unit_graph::UnitGraph> {
+        let mut cmd = self
+            .arg("-Z")
+            .arg("unstable-options")
+            .arg("--unit-graph")
+            .spawn_command()?;
+        let output = cmd
+            .output()
+            .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+        if !output.status.success() {
+            return Err(
+                CargoError::new(ErrorKind::CommandFailed).set_context(format!(
+                    "Command: {}\n{}",
+                    shell::redacted_command_line(&cmd),
+                    String::from_utf8_lossy(&output.stderr)
+                )),
+            );
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))
+    }
+}
+
+/// Targets selected for building, to validate [`CargoBuild::run`] and [`CargoBuild::run_tests`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+struct Selection {
+    lib: bool,
+    all_targets: bool,
+    all_bins: bool,
+    bins: Vec<OsArg>,
+    all_examples: bool,
+    examples: Vec<OsArg>,
+    all_tests: bool,
+    tests: Vec<OsArg>,
+    all_benches: bool,
+    benches: Vec<OsArg>,
+}
+
+impl Selection {
+    fn lower(&self, cmd: &mut process::Command) {
+        if self.lib {
+            cmd.arg("--lib");
+        }
+        if self.all_bins {
+            cmd.arg("--bins");
+        }
+        for name in &self.bins {
+            cmd.arg("--bin").arg(name);
+        }
+        if self.all_examples {
+            cmd.arg("--examples");
+        }
+        for name in &self.examples {
+            cmd.arg("--example").arg(name);
+        }
+        if self.all_tests {
+            cmd.arg("--tests");
+        }
+        for name in &self.tests {
+            cmd.arg("--test").arg(name);
+        }
+        if self.all_benches {
+            cmd.arg("--benches");
+        }
+        for name in &self.benches {
+            cmd.arg("--bench").arg(name);
+        }
+        if self.all_targets {
+            cmd.arg("--all-targets");
+        }
+    }
+
+    fn is_bin(&self) -> bool {
+        self.all_bins || !self.bins.is_empty()
+    }
+
+    fn is_example(&self) -> bool {
+        self.all_examples || !self.examples.is_empty()
+    }
+
+    fn is_test(&self) -> bool {
+        self.all_tests || !self.tests.is_empty() || self.all_benches || !self.benches.is_empty()
+    }
+
+    fn is_explicit(&self) -> bool {
+        self.lib || self.all_targets || self.is_bin() || self.is_example() || self.is_test()
+    }
+
+    /// The `kind` of the single runnable artifact being selected.
+    fn runnable_kind(&self) -> CargoResult<&'static str> {
+        match (self.is_bin(), self.is_example()) {
+            (true, true) => Err(CargoError::new(ErrorKind::CommandFailed)
+                .set_context("Ambiguous which binary is intended, multiple selected")),
+            (true, false) => {
+                check_single("binary", self.all_bins, &self.bins)?;
+                Ok("bin")
+            }
+            (false, true) => {
+                check_single("example", self.all_examples, &self.examples)?;
+                Ok("example")
+            }
+            (false, false) => {
+                if self.is_explicit() && !self.all_targets {
+                    Err(CargoError::new(ErrorKind::CommandFailed).set_context(
+                        "No binaries selected, see `bin()`, `bins()`, `example()`, or `examples()`",
+                    ))
+                } else {
+                    Ok("bin")
+                }
+            }
+        }
+    }
+
+    /// Ensure test artifacts will be built.
+    #[cfg(feature = "test_unstable")]
+    fn check_tests(&self) -> CargoResult<()> {
+        if self.is_explicit() && !(self.all_targets || self.is_test()) {
+            Err(CargoError::new(ErrorKind::CommandFailed).set_context(
+                "No tests selected, see `test()`, `tests()`, `bench()`, `benches()`, or `all_targets()`",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn check_single(kind: &str, all: bool, names: &[OsArg]) -> CargoResult<()> {
+    let names: Vec<_> = names.iter().map(|n| n.0.to_string_lossy()).collect();
+    let is_glob = |n: &std::borrow::Cow<'_, str>| n.contains(['*', '?', '[']);
+    if !all && 1 < names.len() && !names.iter().any(is_glob) {
+        return Err(
+            CargoError::new(ErrorKind::CommandFailed).set_context(format!(
+                "Ambiguous which {kind} is intended, multiple selected: {}",
+                names.join(", ")
+            )),
+        );
+    }
+    Ok(())
+}
+
+/// A command-line argument, serialized as a UTF-8 string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct OsArg(OsString);
+
+impl OsArg {
+    fn new(arg: &OsStr) -> Self {
+        Self(arg.to_owned())
+    }
+}
+
+impl std::borrow::Borrow<OsStr> for OsArg {
+    fn borrow(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl AsRef<OsStr> for OsArg {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl serde::Serialize for OsArg {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let arg = self
+            .0
+            .to_str()
+            .ok_or_else(|| serde::ser::Error::custom("argument is not UTF-8"))?;
+        serializer.serialize_str(arg)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for OsArg {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let arg = String::deserialize(deserializer)?;
+        Ok(Self(arg.into()))
+    }
 }
 
 impl Default for CargoBuild {
@@ -333,3 +1219,25 @@ impl Default for CargoBuild {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_dir() {
+        let cwd = env::current_dir().unwrap();
+        let fixture = cwd.join("tests/testsuite/fixtures/bin");
+
+        let build = CargoBuild::new();
+        assert_eq!(build.package_dir(&build.resolved_current_dir()), cwd);
+        let build = CargoBuild::new().manifest_path("Cargo.toml");
+        assert_eq!(build.package_dir(&build.resolved_current_dir()), cwd);
+        let build = CargoBuild::new().current_dir("tests/testsuite/fixtures/bin/src");
+        assert_eq!(build.package_dir(&build.resolved_current_dir()), fixture);
+        let build = CargoBuild::new()
+            .current_dir("tests/testsuite/fixtures/bin")
+            .manifest_path("Cargo.toml");
+        assert_eq!(build.package_dir(&build.resolved_current_dir()), fixture);
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::env;
+use std::path;
+use std::sync;
+
+use crate::build::CargoBuild;
+use crate::error::CargoResult;
+use crate::run::CargoRun;
+
+/// Relative paths depend on the working directory, so it is part of the key.
+type Key = (CargoBuild, Option<path::PathBuf>);
+
+type Entry = sync::Arc<sync::Mutex<Option<CargoRun>>>;
+
+static RUNS: sync::OnceLock<sync::Mutex<HashMap<Key, Entry>>> = sync::OnceLock::new();
+
+fn runs() -> sync::MutexGuard<'static, HashMap<Key, Entry>> {
+    RUNS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn key(build: &CargoBuild) -> Key {
+    (build.clone(), env::current_dir().ok())
+}
+
+/// Build `build` once per process, returning the cached [`CargoRun`] afterwards.
+///
+/// Concurrent requests for the same configuration wait on the first.  Failures aren't cached.
+pub(crate) fn run(build: CargoBuild) -> CargoResult<CargoRun> {
+    let entry = runs().entry(key(&build)).or_default().clone();
+    let mut run = entry.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(run) = run.as_ref() {
+        return Ok(run.clone());
+    }
+    let built = build.run()?;
+    *run = Some(built.clone());
+    Ok(built)
+}
+
+pub(crate) fn invalidate(build: &CargoBuild) {
+    runs().remove(&key(build));
+}
+
+/// Forget every [`CargoBuild::run_cached`] result, so the next call builds again.
+pub fn clear_run_cache() {
+    runs().clear();
+}
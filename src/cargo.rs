@@ -1,13 +1,36 @@
 use std::env;
 use std::ffi;
+use std::path;
 use std::process;
 use std::str;
 
 use crate::build::CargoBuild;
+use crate::error::{CargoError, CargoResult, ErrorKind};
+use crate::explain;
+use crate::target::{self, TargetCfg};
+use crate::version::{self, CargoVersion, RustcVersion};
 
 /// The current process' target triplet.
 pub const CURRENT_TARGET: &str = include_str!(concat!(env!("OUT_DIR"), "/current_target.txt"));
 
+/// The cargo profile the current process was built with, like `dev`, `release`, or a custom
+/// profile.
+///
+/// This is the profile escargot was built with which, unless overridden per-package, is the
+/// profile of the crate linking escargot.
+pub const CURRENT_PROFILE: &str = include_str!(concat!(env!("OUT_DIR"), "/current_profile.txt"));
+
+/// The optimization level the current process was built with, like `0`, `3`, or `s`.
+///
+/// Like [`CURRENT_PROFILE`], this reflects how escargot was built.
+///
+/// There is no equivalent for the calling crate's enabled features: escargot's build script only
+/// sees escargot's own features, as cargo doesn't expose a dependent's features to its
+/// dependencies.  Forward them with [`CargoBuild::features`][crate::CargoBuild::features],
+/// like `cfg!(feature = "foo")`.
+pub const CURRENT_OPT_LEVEL: &str =
+    include_str!(concat!(env!("OUT_DIR"), "/current_opt_level.txt"));
+
 fn cargo_bin() -> &'static ffi::OsStr {
     static CARGO_BIN: std::sync::OnceLock<ffi::OsString> = std::sync::OnceLock::new();
     CARGO_BIN.get_or_init(|| env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
@@ -22,11 +45,47 @@ pub struct Cargo {
 impl Cargo {
     /// Create a top-level command.
     pub fn new() -> Self {
+        Self::with_cargo_path(cargo_bin())
+    }
+
+    /// Create a top-level command, running the `cargo` at `path`.
+    pub fn with_cargo_path<S: AsRef<ffi::OsStr>>(path: S) -> Self {
         Self {
-            cmd: process::Command::new(cargo_bin()),
+            cmd: process::Command::new(path),
         }
     }
 
+    /// Wrap `cmd`, sharing its program, environment, and working directory.
+    pub(crate) fn with_command(cmd: process::Command) -> Self {
+        Self { cmd }
+    }
+
+    /// Create a top-level command, running the `cargo` from a rustup `toolchain`.
+    ///
+    /// Fails with [`ErrorKind::ToolchainNotInstalled`] if `toolchain` is not installed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// escargot::Cargo::toolchain("nightly")
+    ///     .unwrap()
+    ///     .build()
+    ///     .bin("bin")
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .exec()
+    ///     .unwrap();
+    /// ```
+    pub fn toolchain<S: AsRef<str>>(toolchain: S) -> CargoResult<Self> {
+        let toolchain = toolchain.as_ref();
+        let path = rustup_which(toolchain, "cargo")?;
+        let mut cargo = Self::with_cargo_path(path);
+        // Ensure any rustup proxies cargo calls into, like `rustc`, agree on the toolchain
+        cargo.cmd.env("RUSTUP_TOOLCHAIN", toolchain);
+        Ok(cargo)
+    }
+
     /// Manually pass an argument that is unsupported.
     ///
     /// Caution: Passing in a sub-command or `--` can throw off the API.
@@ -58,6 +117,201 @@ impl Cargo {
     pub fn into_command(self) -> process::Command {
         self.cmd
     }
+
+    /// Report the version of cargo being run.
+    ///
+    /// This is cached per cargo binary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let version = escargot::Cargo::new().version().unwrap();
+    /// assert!(escargot::Version::new(1, 0, 0) < version.version);
+    /// ```
+    pub fn version(&self) -> CargoResult<CargoVersion> {
+        static CACHE: std::sync::OnceLock<version::VersionCache<CargoVersion>> =
+            std::sync::OnceLock::new();
+        let cmd = self.sibling_command(self.cmd.get_program());
+        version::query_cached(&CACHE, cmd, CargoVersion::parse)
+    }
+
+    /// Report the version of rustc that cargo will run.
+    ///
+    /// This is cached per rustc binary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let version = escargot::Cargo::new().rustc_version().unwrap();
+    /// assert_eq!(version.host.as_deref(), Some(escargot::CURRENT_TARGET));
+    /// ```
+    pub fn rustc_version(&self) -> CargoResult<RustcVersion> {
+        static CACHE: std::sync::OnceLock<version::VersionCache<RustcVersion>> =
+            std::sync::OnceLock::new();
+        let cmd = self.rustc_command();
+        version::query_cached(&CACHE, cmd, RustcVersion::parse)
+    }
+
+    /// The long explanation for an error code, like `E0308`, from `rustc --explain`.
+    ///
+    /// Returns `None` for codes rustc doesn't explain, like lints.  This is cached per
+    /// toolchain.  See [`code_url`][crate::code_url] for linking to the documentation instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let explanation = escargot::Cargo::new().explain("E0308").unwrap().unwrap();
+    /// assert!(explanation.contains("Expected type did not match the received type."));
+    /// ```
+    pub fn explain<S: AsRef<str>>(&self, code: S) -> CargoResult<Option<String>> {
+        let version = self.rustc_version()?;
+        let toolchain = format!(
+            "{} {}",
+            version.version,
+            version.commit_hash.unwrap_or_default()
+        );
+        explain::query(self.rustc_command(), toolchain, code.as_ref())
+    }
+
+    /// Report the configuration of the `triple` target, like its OS and pointer width.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let cfg = escargot::Cargo::new()
+    ///     .target_cfg(escargot::CURRENT_TARGET)
+    ///     .unwrap();
+    /// assert_eq!(cfg.os(), Some(std::env::consts::OS));
+    /// ```
+    pub fn target_cfg<S: AsRef<str>>(&self, triple: S) -> CargoResult<TargetCfg> {
+        let triple = triple.as_ref();
+        let raw = target::print(self.rustc_command(), &["cfg", "--target", triple])?;
+        Ok(TargetCfg::parse(triple, &raw))
+    }
+
+    /// Report all target triplets rustc knows about.
+    ///
+    /// These are not necessarily installed, see [`Cargo::is_target_installed`].
+    pub fn target_list(&self) -> CargoResult<Vec<String>> {
+        let raw = target::print(self.rustc_command(), &["target-list"])?;
+        Ok(raw
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_owned())
+            .collect())
+    }
+
+    /// Report the root of the toolchain rustc uses.
+    pub fn sysroot(&self) -> CargoResult<path::PathBuf> {
+        let raw = target::print(self.rustc_command(), &["sysroot"])?;
+        Ok(path::PathBuf::from(raw.trim_end()))
+    }
+
+    /// Whether the standard library for `triple` is installed, so it can be built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let cargo = escargot::Cargo::new();
+    /// assert!(cargo.is_target_installed(escargot::CURRENT_TARGET).unwrap());
+    /// ```
+    pub fn is_target_installed<S: AsRef<str>>(&self, triple: S) -> CargoResult<bool> {
+        let sysroot = self.sysroot()?;
+        Ok(target::is_installed(&sysroot, triple.as_ref()))
+    }
+
+    /// The `rustc` cargo will run, with the same environment.
+    pub(crate) fn rustc_command(&self) -> process::Command {
+        let configured = self
+            .cmd
+            .get_envs()
+            .find(|(k, _)| *k == "RUSTC")
+            .map(|(_, v)| v.map(|v| v.to_owned()))
+            .unwrap_or_else(|| env::var_os("RUSTC"));
+        let rustc = configured.unwrap_or_else(|| {
+            // Prefer the `rustc` distributed with the selected cargo
+            let cargo = path::Path::new(self.cmd.get_program());
+            let sibling = cargo.with_file_name(format!("rustc{}", env::consts::EXE_SUFFIX));
+            if cargo.parent().is_some_and(|p| !p.as_os_str().is_empty()) && sibling.exists() {
+                sibling.into_os_string()
+            } else {
+                "rustc".into()
+            }
+        });
+        self.sibling_command(&rustc)
+    }
+
+    fn sibling_command(&self, program: &ffi::OsStr) -> process::Command {
+        let mut cmd = process::Command::new(program);
+        for (k, v) in self.cmd.get_envs() {
+            match v {
+                Some(v) => cmd.env(k, v),
+                None => cmd.env_remove(k),
+            };
+        }
+        if let Some(dir) = self.cmd.get_current_dir() {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+}
+
+/// Find `bin` for `toolchain`, without installing it.
+pub(crate) fn rustup_which(toolchain: &str, bin: &str) -> CargoResult<path::PathBuf> {
+    if !is_toolchain_installed(toolchain)? {
+        return Err(CargoError::new(ErrorKind::ToolchainNotInstalled)
+            .set_context(format!("toolchain '{toolchain}' is not installed")));
+    }
+    let output = rustup()
+        .args(["which", "--toolchain", toolchain, bin])
+        .output()
+        .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+    if !output.status.success() {
+        return Err(CargoError::new(ErrorKind::CommandFailed)
+            .set_context(String::from_utf8_lossy(&output.stderr)));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
+    Ok(path::PathBuf::from(stdout.trim_end()))
+}
+
+/// Whether `rustup toolchain list` has `toolchain`, by name or with the host triple appended.
+///
+/// Paths to toolchains are left for `rustup which` to check.
+fn is_toolchain_installed(toolchain: &str) -> CargoResult<bool> {
+    if toolchain.contains(['/', '\\']) {
+        return Ok(true);
+    }
+    let output = rustup().args(["toolchain", "list"]).output().map_err(|e| {
+        CargoError::new(ErrorKind::InvalidCommand)
+            .set_context("rustup is required to select a toolchain")
+            .set_cause(e)
+    })?;
+    if !output.status.success() {
+        return Err(CargoError::new(ErrorKind::CommandFailed)
+            .set_context(String::from_utf8_lossy(&output.stderr)));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
+    // rustup names toolchains after the host, which differs from `CURRENT_TARGET` when escargot
+    // was cross-compiled
+    let host = Cargo::new().rustc_version()?.host;
+    Ok(is_listed(&stdout, toolchain, host.as_deref()))
+}
+
+fn is_listed(list: &str, toolchain: &str, host: Option<&str>) -> bool {
+    let with_host = host.map(|host| format!("{toolchain}-{host}"));
+    list.lines()
+        .filter_map(|l| l.split_whitespace().next())
+        .any(|name| name == toolchain || Some(name) == with_host.as_deref())
+}
+
+fn rustup() -> process::Command {
+    let mut cmd = process::Command::new("rustup");
+    cmd.env("RUSTUP_AUTO_INSTALL", "0")
+        .env("RUST_BACKTRACE", "0");
+    cmd
 }
 
 impl Default for Cargo {
@@ -65,3 +319,22 @@ impl Default for Cargo {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toolchain_list() {
+        let list = "stable-aarch64-apple-darwin (active, default)\nnightly-2026-05-15-aarch64-apple-darwin\nmy-toolchain\n";
+        let host = Some("aarch64-apple-darwin");
+        assert!(is_listed(list, "stable", host));
+        assert!(is_listed(list, "stable-aarch64-apple-darwin", host));
+        assert!(is_listed(list, "nightly-2026-05-15", host));
+        assert!(is_listed(list, "my-toolchain", host));
+        assert!(!is_listed(list, "nightly", host));
+        assert!(!is_listed(list, "1.95", host));
+        assert!(!is_listed(list, "stable", Some("x86_64-unknown-linux-gnu")));
+        assert!(!is_listed(list, "stable", None));
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::format;
+use crate::format::diagnostic::{Diagnostic, DiagnosticLevel};
+use crate::summary::package_name;
+
+/// Diagnostics from a build, deduplicated across compilation units.
+///
+/// Cargo reports a diagnostic once per unit that compiles the code, so a warning in a library
+/// shows up for the lib and its unit tests.  Diagnostics with the same code, level, primary
+/// span, and message are collected once, remembering every [`format::Target`] that reported
+/// them.
+///
+/// # Example
+///
+/// ```rust
+/// # let target_dir = tempfile::TempDir::new().unwrap();
+/// let msgs: Vec<_> = escargot::CargoBuild::new()
+///     .manifest_path("tests/testsuite/fixtures/warn/Cargo.toml")
+///     .target_dir(target_dir.path())
+///     .exec()
+///     .unwrap()
+///     .map(|m| m.unwrap())
+///     .collect();
+/// let msgs: Vec<_> = msgs.iter().map(|m| m.decode().unwrap()).collect();
+///
+/// let mut collector = escargot::DiagnosticCollector::new();
+/// for msg in &msgs {
+///     collector.add(msg);
+/// }
+/// let filter = escargot::DiagnosticFilter::new()
+///     .level(escargot::format::diagnostic::DiagnosticLevel::Warning)
+///     .path("src/**/*.rs");
+/// for collected in collector.filter(&filter) {
+///     println!("{}", collected.diagnostic.message);
+/// }
+/// # assert_eq!(collector.filter(&filter).count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticCollector<'a> {
+    diagnostics: Vec<CollectedDiagnostic<'a>>,
+    index: HashMap<Key, usize>,
+}
+
+impl<'a> DiagnosticCollector<'a> {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record `msg` if it is a compiler message.
+    ///
+    /// Returns whether the diagnostic had not been seen before.
+    pub fn add(&mut self, msg: &format::Message<'a>) -> bool {
+        match msg {
+            format::Message::CompilerMessage(message) => self.add_compiler_message(message),
+            _ => false,
+        }
+    }
+
+    /// Record `message`.
+    ///
+    /// Returns whether the diagnostic had not been seen before.
+    pub fn add_compiler_message(&mut self, message: &format::FromCompiler<'a>) -> bool {
+        let key = Key::new(&message.message);
+        if let Some(&i) = self.index.get(&key) {
+            let targets = &mut self.diagnostics[i].targets;
+            if !targets.contains(&message.target) {
+                targets.push(message.target.clone());
+            }
+            return false;
+        }
+        self.index.insert(key, self.diagnostics.len());
+        self.diagnostics.push(CollectedDiagnostic {
+            package_id: message.package_id.clone(),
+            diagnostic: message.message.clone(),
+            targets: vec![message.target.clone()],
+        });
+        true
+    }
+
+    /// Every diagnostic, in the order first reported
+    pub fn diagnostics(&self) -> impl Iterator<Item = &CollectedDiagnostic<'a>> {
+        self.diagnostics.iter()
+    }
+
+    /// Diagnostics matching `filter`, in the order first reported
+    pub fn filter<'s>(
+        &'s self,
+        filter: &'s DiagnosticFilter,
+    ) -> impl Iterator<Item = &'s CollectedDiagnostic<'a>> {
+        self.diagnostics.iter().filter(|d| filter.matches(d))
+    }
+
+    /// Number of distinct diagnostics
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Whether no diagnostics were collected
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// A diagnostic in a [`DiagnosticCollector`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CollectedDiagnostic<'a> {
+    /// The package that first reported the diagnostic
+    pub package_id: format::WorkspaceMember<'a>,
+    /// The diagnostic, as first reported
+    pub diagnostic: Diagnostic<'a>,
+    /// Every target that reported the diagnostic
+    pub targets: Vec<format::Target<'a>>,
+}
+
+/// What makes two diagnostics the same
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    code: Option<String>,
+    level: DiagnosticLevel,
+    span: Option<(String, usize, usize, usize, usize)>,
+    message: String,
+}
+
+impl Key {
+    fn new(diagnostic: &Diagnostic<'_>) -> Self {
+        let span = diagnostic.primary_span().map(|s| {
+            (
+                s.file_name.to_string_lossy().into_owned(),
+                s.line_start,
+                s.column_start,
+                s.line_end,
+                s.column_end,
+            )
+        });
+        Self {
+            code: diagnostic.code.as_ref().map(|c| c.code.as_ref().to_owned()),
+            level: diagnostic.level,
+            span,
+            message: diagnostic.message.as_ref().to_owned(),
+        }
+    }
+}
+
+/// Select diagnostics from a [`DiagnosticCollector`].
+///
+/// Each criterion accepts any of the values given for it, and a diagnostic must satisfy every
+/// criterion that was set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticFilter {
+    levels: Vec<DiagnosticLevel>,
+    code_prefixes: Vec<String>,
+    packages: Vec<String>,
+    paths: Vec<String>,
+}
+
+impl DiagnosticFilter {
+    /// Accept every diagnostic.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Accept diagnostics at `level`.
+    pub fn level(mut self, level: DiagnosticLevel) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    /// Accept diagnostics whose code starts with `prefix`, like `clippy::` or `E0`.
+    pub fn code_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.code_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Accept diagnostics first reported by the package named `name`.
+    pub fn package(mut self, name: impl Into<String>) -> Self {
+        self.packages.push(name.into());
+        self
+    }
+
+    /// Accept diagnostics whose primary span is in a file matching `glob`.
+    ///
+    /// `*` matches within a path component, `**` across components, and `?` a single
+    /// character.
+    pub fn path(mut self, glob: impl Into<String>) -> Self {
+        self.paths.push(glob.into());
+        self
+    }
+
+    /// Whether `collected` is accepted
+    pub fn matches(&self, collected: &CollectedDiagnostic<'_>) -> bool {
+        let diagnostic = &collected.diagnostic;
+        if !self.levels.is_empty() && !self.levels.contains(&diagnostic.level) {
+            return false;
+        }
+        if !self.code_prefixes.is_empty() {
+            let code = diagnostic.code.as_ref().map(|c| c.code.as_ref());
+            let Some(code) = code else {
+                return false;
+            };
+            if !self
+                .code_prefixes
+                .iter()
+                .any(|p| code.starts_with(p.as_str()))
+            {
+                return false;
+            }
+        }
+        if !self.packages.is_empty() {
+            let name = package_name(collected.package_id.as_str());
+            let name = name.split('@').next().unwrap_or_default();
+            if !self.packages.iter().any(|p| p == name) {
+                return false;
+            }
+        }
+        if !self.paths.is_empty() {
+            let Some(span) = diagnostic.primary_span() else {
+                return false;
+            };
+            let file = span.file_name.to_string_lossy().replace('\\', "/");
+            if !self
+                .paths
+                .iter()
+                .any(|g| glob_match(g.as_bytes(), file.as_bytes()))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn glob_match(glob: &[u8], path: &[u8]) -> bool {
+    match glob {
+        [] => path.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            // Zero or more whole components
+            glob_match(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(i, c)| *c == b'/' && glob_match(rest, &path[i + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| glob_match(rest, &path[i..])),
+        [b'*', rest @ ..] => {
+            let component = path.iter().position(|c| *c == b'/').unwrap_or(path.len());
+            (0..=component).any(|i| glob_match(rest, &path[i..]))
+        }
+        [b'?', rest @ ..] => match path {
+            [c, path @ ..] if *c != b'/' => glob_match(rest, path),
+            _ => false,
+        },
+        [g, rest @ ..] => match path {
+            [c, path @ ..] if c == g => glob_match(rest, path),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupe() {
+        // The same warning from the `lib` target, repeated, then from the `bin` target, followed
+        // by a different lint
+        let raws = [
+            r#"{"reason":"compiler-message","package_id":"path+file:///tmp/app#0.1.0","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/tmp/app/src/lib.rs"},"message":{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/lib.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[]}],"children":[]}}"#,
+            r#"{"reason":"compiler-message","package_id":"path+file:///tmp/app#0.1.0","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/tmp/app/src/lib.rs"},"message":{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/lib.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[]}],"children":[]}}"#,
+            r#"{"reason":"compiler-message","package_id":"path+file:///tmp/app#0.1.0","target":{"kind":["bin"],"crate_types":["bin"],"name":"app","src_path":"/tmp/app/src/main.rs"},"message":{"message":"unused variable: `a`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/lib.rs","byte_start":20,"byte_end":21,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"text":[]}],"children":[]}}"#,
+            r#"{"reason":"compiler-message","package_id":"path+file:///tmp/app#0.1.0","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/tmp/app/src/lib.rs"},"message":{"message":"this let-binding has unit value","code":{"code":"clippy::let_unit_value"},"level":"warning","spans":[{"file_name":"src/util/mod.rs","byte_start":40,"byte_end":55,"line_start":4,"line_end":4,"column_start":5,"column_end":20,"is_primary":true,"text":[]}],"children":[]}}"#,
+        ];
+        let msgs: Vec<format::Message<'_>> = raws
+            .iter()
+            .map(|raw| serde_json::from_str(raw).unwrap())
+            .collect();
+        let mut collector = DiagnosticCollector::new();
+        let added: Vec<_> = msgs.iter().map(|msg| collector.add(msg)).collect();
+        assert_eq!(added, [true, false, false, true]);
+        assert_eq!(collector.len(), 2);
+
+        let first = collector.diagnostics().next().unwrap();
+        let kinds: Vec<_> = first.targets.iter().map(|t| t.kind[0].as_ref()).collect();
+        assert_eq!(kinds, ["lib", "bin"]);
+
+        let count = |filter: DiagnosticFilter| collector.filter(&filter).count();
+        assert_eq!(count(DiagnosticFilter::new()), 2);
+        assert_eq!(
+            count(DiagnosticFilter::new().level(DiagnosticLevel::Error)),
+            0
+        );
+        assert_eq!(count(DiagnosticFilter::new().code_prefix("clippy::")), 1);
+        assert_eq!(count(DiagnosticFilter::new().package("app")), 2);
+        assert_eq!(count(DiagnosticFilter::new().package("other")), 0);
+        assert_eq!(count(DiagnosticFilter::new().path("src/*.rs")), 1);
+        assert_eq!(count(DiagnosticFilter::new().path("src/**/*.rs")), 2);
+        assert_eq!(
+            count(
+                DiagnosticFilter::new()
+                    .level(DiagnosticLevel::Warning)
+                    .path("**/mod.rs")
+                    .code_prefix("unused")
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn glob() {
+        let matches = |glob: &str, path: &str| glob_match(glob.as_bytes(), path.as_bytes());
+        assert!(matches("src/*.rs", "src/lib.rs"));
+        assert!(!matches("src/*.rs", "src/a/lib.rs"));
+        assert!(matches("src/**/*.rs", "src/lib.rs"));
+        assert!(matches("src/**/*.rs", "src/a/b/lib.rs"));
+        assert!(matches("**", "src/a/lib.rs"));
+        assert!(matches("src/li?.rs", "src/lib.rs"));
+        assert!(!matches("src/?", "src/a/"));
+    }
+}
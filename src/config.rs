@@ -0,0 +1,120 @@
+use std::fmt;
+
+/// A value for a `--config KEY=VALUE` override.
+///
+/// Rendered as TOML, so strings are quoted and escaped.  See
+/// [`CargoBuild::config`][crate::CargoBuild::config].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConfigValue {
+    /// A TOML string
+    String(String),
+    /// A TOML integer
+    Integer(i64),
+    /// A TOML boolean
+    Boolean(bool),
+    /// A TOML array
+    Array(Vec<ConfigValue>),
+}
+
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        '\r' => f.write_str("\\r")?,
+                        '\t' => f.write_str("\\t")?,
+                        c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&std::path::Path> for ConfigValue {
+    fn from(value: &std::path::Path) -> Self {
+        Self::String(value.to_string_lossy().into_owned())
+    }
+}
+
+impl From<i64> for ConfigValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<u32> for ConfigValue {
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl<T: Into<ConfigValue>> From<Vec<T>> for ConfigValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<ConfigValue>, const N: usize> From<[T; N]> for ConfigValue {
+    fn from(values: [T; N]) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(ConfigValue::from("plain").to_string(), r#""plain""#);
+        assert_eq!(
+            ConfigValue::from("C:\\dir \"quoted\"\n").to_string(),
+            r#""C:\\dir \"quoted\"\n""#
+        );
+        assert_eq!(ConfigValue::from("\u{1}").to_string(), r#""\u0001""#);
+        assert_eq!(ConfigValue::from(3_u32).to_string(), "3");
+        assert_eq!(ConfigValue::from(true).to_string(), "true");
+        assert_eq!(
+            ConfigValue::from(["-C", "opt-level=1"]).to_string(),
+            r#"["-C", "opt-level=1"]"#
+        );
+    }
+}
@@ -8,6 +8,7 @@ pub type CargoResult<T> = Result<T, CargoError>;
 
 /// For programmatically processing failures.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Spawning the cargo subommand failed.
     InvalidCommand,
@@ -15,6 +16,10 @@ pub enum ErrorKind {
     CommandFailed,
     /// Parsing the cargo subcommand's output failed.
     InvalidOutput,
+    /// The requested toolchain is not installed.
+    ToolchainNotInstalled,
+    /// The requested profile is not defined.
+    UndefinedProfile,
 }
 
 impl fmt::Display for ErrorKind {
@@ -23,6 +28,10 @@ impl fmt::Display for ErrorKind {
             ErrorKind::InvalidOutput => write!(f, "Spawning the cargo subommand failed."),
             ErrorKind::CommandFailed => write!(f, "The cargo subcommand returned an error."),
             ErrorKind::InvalidCommand => write!(f, "Parsing the cargo subcommand's output failed."),
+            ErrorKind::ToolchainNotInstalled => {
+                write!(f, "The requested toolchain is not installed.")
+            }
+            ErrorKind::UndefinedProfile => write!(f, "The requested profile is not defined."),
         }
     }
 }
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::process;
+use std::sync;
+
+use crate::error::{CargoError, CargoResult, ErrorKind};
+
+/// Documentation for a diagnostic code, chosen by its namespace.
+///
+/// - rustc error codes, like `E0308`, link to the error index
+/// - `clippy::` lints link to the Clippy lint list
+/// - `rustdoc::` lints link to the rustdoc lints chapter
+///
+/// Returns `None` for other codes, like rustc lints.
+///
+/// # Example
+///
+/// ```rust
+/// assert_eq!(
+///     escargot::code_url("E0308").as_deref(),
+///     Some("https://doc.rust-lang.org/error_codes/E0308.html")
+/// );
+/// assert_eq!(escargot::code_url("unused_variables"), None);
+/// ```
+pub fn code_url(code: &str) -> Option<String> {
+    if let Some(lint) = code.strip_prefix("clippy::") {
+        return Some(format!(
+            "https://rust-lang.github.io/rust-clippy/master/index.html#{lint}"
+        ));
+    }
+    if let Some(lint) = code.strip_prefix("rustdoc::") {
+        return Some(format!(
+            "https://doc.rust-lang.org/rustdoc/lints.html#{lint}"
+        ));
+    }
+    is_error_code(code).then(|| format!("https://doc.rust-lang.org/error_codes/{code}.html"))
+}
+
+/// Whether `code` is a rustc error code, like `E0308`
+fn is_error_code(code: &str) -> bool {
+    code.strip_prefix('E')
+        .is_some_and(|digits| digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+type ExplainCache = sync::Mutex<HashMap<(String, String), Option<String>>>;
+
+/// Run `rustc --explain code`, caching the result by `toolchain`.
+pub(crate) fn query(
+    mut rustc: process::Command,
+    toolchain: String,
+    code: &str,
+) -> CargoResult<Option<String>> {
+    static CACHE: sync::OnceLock<ExplainCache> = sync::OnceLock::new();
+
+    if !is_error_code(code) {
+        return Ok(None);
+    }
+    let key = (toolchain, code.to_owned());
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(explanation) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(explanation.clone());
+    }
+
+    let output = rustc
+        .arg("--explain")
+        .arg(code)
+        .output()
+        .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+    let explanation = if output.status.success() {
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
+        Some(stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.contains("is not a valid error code") {
+            return Err(CargoError::new(ErrorKind::CommandFailed).set_context(stderr));
+        }
+        None
+    };
+
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, explanation.clone());
+    Ok(explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        assert_eq!(
+            code_url("clippy::needless_return").as_deref(),
+            Some("https://rust-lang.github.io/rust-clippy/master/index.html#needless_return")
+        );
+        assert_eq!(
+            code_url("rustdoc::broken_intra_doc_links").as_deref(),
+            Some("https://doc.rust-lang.org/rustdoc/lints.html#broken_intra_doc_links")
+        );
+        assert_eq!(code_url("E030"), None);
+        assert_eq!(code_url("Eabcd"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Generate temporary Cargo projects for tests.
+//!
+//! # Example
+//!
+//! ```rust
+//! let project = escargot::fixture::Project::new()
+//!     .file("src/main.rs", r#"fn main() { println!("Hello"); }"#)
+//!     .create()
+//!     .unwrap();
+//! let run = project.build().run().unwrap();
+//! let output = run.command().output().unwrap();
+//! assert_eq!(output.stdout, b"Hello\n");
+//! ```
+
+use std::env;
+use std::fs;
+use std::path;
+use std::process;
+use std::sync::atomic;
+use std::time;
+
+use crate::build::CargoBuild;
+use crate::error::{CargoError, CargoResult, ErrorKind};
+
+const DEFAULT_MANIFEST: &str = r#"[package]
+name = "fixture"
+version = "0.0.0"
+edition = "2021"
+publish = false
+"#;
+
+/// Inline description of a Cargo project or workspace.
+///
+/// Without a [`Project::manifest`], this is a package named `fixture`.
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    manifest: Option<String>,
+    files: Vec<(path::PathBuf, String)>,
+}
+
+impl Project {
+    /// Describe an empty project.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contents of the root `Cargo.toml`
+    pub fn manifest<S: Into<String>>(mut self, contents: S) -> Self {
+        self.manifest = Some(contents.into());
+        self
+    }
+
+    /// Add a file, relative to the project root.
+    ///
+    /// Workspace members are added by including their `Cargo.toml`, like `crates/a/Cargo.toml`.
+    pub fn file<P: AsRef<path::Path>, S: Into<String>>(mut self, path: P, contents: S) -> Self {
+        self.files.push((path.as_ref().to_owned(), contents.into()));
+        self
+    }
+
+    /// Write the project to a new temporary directory.
+    pub fn create(self) -> CargoResult<ProjectDir> {
+        let root = temp_dir().map_err(|e| {
+            CargoError::new(ErrorKind::InvalidCommand)
+                .set_context("Failed to create fixture directory")
+                .set_cause(e)
+        })?;
+        let project = ProjectDir { root };
+
+        let manifest = self.manifest.unwrap_or_else(|| DEFAULT_MANIFEST.to_owned());
+        let files =
+            std::iter::once((path::PathBuf::from("Cargo.toml"), manifest)).chain(self.files);
+        for (relative, contents) in files {
+            let path = project.root.join(&relative);
+            let written = path
+                .parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .and_then(|()| fs::write(&path, contents));
+            written.map_err(|e| {
+                CargoError::new(ErrorKind::InvalidCommand)
+                    .set_context(format!("Failed to write fixture {}", relative.display()))
+                    .set_cause(e)
+            })?;
+        }
+        Ok(project)
+    }
+}
+
+/// A [`Project`] written to a temporary directory, deleted on drop.
+#[derive(Debug)]
+pub struct ProjectDir {
+    root: path::PathBuf,
+}
+
+impl ProjectDir {
+    /// Directory containing the project
+    pub fn root(&self) -> &path::Path {
+        &self.root
+    }
+
+    /// Path to the root `Cargo.toml`, for [`CargoBuild::manifest_path`]
+    pub fn manifest_path(&self) -> path::PathBuf {
+        self.root.join("Cargo.toml")
+    }
+
+    /// Path for build artifacts, for [`CargoBuild::target_dir`]
+    pub fn target_dir(&self) -> path::PathBuf {
+        self.root.join("target")
+    }
+
+    /// A [`CargoBuild`] for this project.
+    pub fn build(&self) -> CargoBuild {
+        CargoBuild::new()
+            .manifest_path(self.manifest_path())
+            .target_dir(self.target_dir())
+    }
+}
+
+impl Drop for ProjectDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.root) {
+            log::debug!("Failed to remove fixture {}: {err}", self.root.display());
+        }
+    }
+}
+
+fn temp_dir() -> std::io::Result<path::PathBuf> {
+    static COUNTER: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+    let nanos = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    loop {
+        let count = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+        let name = format!("escargot-fixture-{}-{nanos:x}-{count}", process::id());
+        let dir = env::temp_dir().join(name);
+        // Not `create_dir_all`, to detect collisions
+        #[allow(clippy::create_dir)]
+        match fs::create_dir(&dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            result => return result.map(|()| dir),
+        }
+    }
+}
@@ -93,6 +93,50 @@ pub struct DiagnosticSpan<'a> {
     pub expansion: Option<Box<DiagnosticSpanMacroExpansion<'a>>>,
 }
 
+impl<'a> DiagnosticSpan<'a> {
+    /// The macro expansions that produced this span, innermost first.
+    ///
+    /// Each expansion has the macro's name, the span it was called from, and where it was
+    /// defined, if known.
+    pub fn macro_backtrace(&self) -> impl Iterator<Item = &DiagnosticSpanMacroExpansion<'a>> {
+        std::iter::successors(self.expansion.as_deref(), |e| e.span.expansion.as_deref())
+    }
+
+    /// The outermost span in the workspace that led to this one, following macro call sites.
+    ///
+    /// This is the user-written code responsible for a diagnostic reported inside a macro.
+    /// Relative paths are assumed to be in the workspace, as rustc reports them relative to
+    /// it.
+    pub fn workspace_call_site(&self, workspace_root: &path::Path) -> Option<&DiagnosticSpan<'a>> {
+        std::iter::once(self)
+            .chain(self.macro_backtrace().map(|e| &e.span))
+            .filter(|s| s.is_in_workspace(workspace_root))
+            .last()
+    }
+
+    /// Whether this span comes from the expansion of a macro defined outside of the workspace.
+    pub fn is_from_external_macro(&self, workspace_root: &path::Path) -> bool {
+        self.expansion
+            .as_ref()
+            .map(|e| {
+                e.def_site_span
+                    .as_ref()
+                    .map(|s| !s.is_in_workspace(workspace_root))
+                    .unwrap_or(true)
+            })
+            .unwrap_or(false)
+    }
+
+    fn is_in_workspace(&self, workspace_root: &path::Path) -> bool {
+        if self.file_name.is_absolute() {
+            self.file_name.starts_with(workspace_root)
+        } else {
+            // Built-in and external macros are reported as `<...>`
+            !self.file_name.to_string_lossy().starts_with('<')
+        }
+    }
+}
+
 /// Whether a suggestion can be safely applied.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Applicability {
@@ -141,8 +185,24 @@ pub struct Diagnostic<'a> {
     pub rendered: Option<CowStr<'a>>,
 }
 
+impl<'a> Diagnostic<'a> {
+    /// The span the diagnostic is reported at, if any
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan<'a>> {
+        self.spans.iter().find(|s| s.is_primary)
+    }
+
+    /// Whether the diagnostic is reported inside a macro defined outside of the workspace.
+    ///
+    /// See [`DiagnosticSpan::is_from_external_macro`].
+    pub fn is_from_external_macro(&self, workspace_root: &path::Path) -> bool {
+        self.primary_span()
+            .map(|s| s.is_from_external_macro(workspace_root))
+            .unwrap_or(false)
+    }
+}
+
 /// The diagnostic level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DiagnosticLevel {
     /// Internal compiler error
@@ -161,3 +221,52 @@ pub enum DiagnosticLevel {
     #[serde(other)]
     Unknown,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(file: &str, line: usize, expansion: &str) -> String {
+        format!(
+            r#"{{"file_name":"{file}","byte_start":0,"byte_end":1,"line_start":{line},"line_end":{line},"column_start":1,"column_end":2,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":{expansion}}}"#
+        )
+    }
+
+    fn expansion(name: &str, call_site: &str, def_site: &str) -> String {
+        format!(r#"{{"span":{call_site},"macro_decl_name":"{name}","def_site_span":{def_site}}}"#)
+    }
+
+    #[test]
+    fn macro_backtrace() {
+        let root = path::Path::new("/work");
+        let dep = "/home/user/.cargo/registry/src/dep-1.0.0/src/lib.rs";
+        let outer = expansion(
+            "local!",
+            &span("src/main.rs", 10, "null"),
+            &span("src/macros.rs", 1, "null"),
+        );
+        let inner = expansion(
+            "dep::make!",
+            &span("/work/src/macros.rs", 3, &outer),
+            &span(dep, 20, "null"),
+        );
+        let raw = span(dep, 22, &inner);
+        let reported: DiagnosticSpan<'_> = serde_json::from_str(&raw).unwrap();
+
+        let names: Vec<_> = reported
+            .macro_backtrace()
+            .map(|e| e.macro_decl_name.as_ref())
+            .collect();
+        assert_eq!(names, ["dep::make!", "local!"]);
+        let call_site = reported.workspace_call_site(root).unwrap();
+        assert_eq!(call_site.file_name, path::Path::new("src/main.rs"));
+        assert_eq!(call_site.line_start, 10);
+        assert!(reported.is_from_external_macro(root));
+
+        let raw = span("src/main.rs", 2, "null");
+        let local: DiagnosticSpan<'_> = serde_json::from_str(&raw).unwrap();
+        assert_eq!(local.macro_backtrace().count(), 0);
+        assert_eq!(local.workspace_call_site(root), Some(&local));
+        assert!(!local.is_from_external_macro(root));
+    }
+}
@@ -1,13 +1,19 @@
 //! Serialization formats for cargo messages.
 
 use std::borrow;
+use std::fmt;
+use std::ops;
 use std::path;
+use std::time;
 
 pub mod diagnostic;
 
 #[cfg(feature = "test_unstable")]
 pub mod test;
 
+#[cfg(feature = "cargo_unstable")]
+pub mod unit_graph;
+
 type CowPath<'a> = borrow::Cow<'a, path::Path>;
 type CowStr<'a> = borrow::Cow<'a, str>;
 
@@ -27,6 +33,9 @@ pub enum Message<'a> {
     /// A build script successfully executed.
     #[serde(borrow)]
     BuildScriptExecuted(BuildScript<'a>),
+    /// A unit finished compiling, with timing information.
+    #[serde(borrow)]
+    TimingInfo(TimingInfo<'a>),
     #[cfg(not(feature = "strict_unstable"))]
     #[doc(hidden)]
     #[serde(other)]
@@ -137,6 +146,13 @@ pub struct WorkspaceMember<'a> {
     raw: CowStr<'a>,
 }
 
+impl WorkspaceMember<'_> {
+    /// The raw package id as given by cargo
+    pub fn as_str(&self) -> &str {
+        self.raw.as_ref()
+    }
+}
+
 /// Profile settings used to determine which compiler flags to use for a
 /// target.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -157,6 +173,21 @@ pub struct ArtifactProfile<'a> {
     pub test: bool,
 }
 
+impl ArtifactProfile<'_> {
+    pub(crate) fn into_owned(self) -> ArtifactProfile<'static> {
+        ArtifactProfile {
+            opt_level: borrow::Cow::Owned(self.opt_level.into_owned()),
+            debuginfo: self.debuginfo.map(|d| match d {
+                DebugInfo::Level(level) => DebugInfo::Level(level),
+                DebugInfo::Name(name) => DebugInfo::Name(borrow::Cow::Owned(name.into_owned())),
+            }),
+            debug_assertions: self.debug_assertions,
+            overflow_checks: self.overflow_checks,
+            test: self.test,
+        }
+    }
+}
+
 /// The amount of debug info. 0 for none, 1 for limited, 2 for full
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
@@ -203,87 +234,489 @@ pub struct BuildScript<'a> {
     pub out_dir: Option<CowPath<'a>>,
     /// The libs to link
     #[serde(borrow)]
-    pub linked_libs: Vec<CowStr<'a>>,
+    pub linked_libs: Vec<LinkedLib<'a>>,
     /// The paths to search when resolving libs
     #[serde(borrow)]
-    pub linked_paths: Vec<CowPath<'a>>,
-    /// The paths to search when resolving libs
+    pub linked_paths: Vec<LinkedPath<'a>>,
+    /// The `cfg`s set for the compilation
     #[serde(borrow)]
-    pub cfgs: Vec<CowPath<'a>>,
+    pub cfgs: Vec<Cfg<'a>>,
     /// The environment variables to add to the compilation
     #[serde(borrow)]
-    pub env: Vec<(CowStr<'a>, CowStr<'a>)>,
+    pub env: Vec<EnvVar<'a>>,
+}
+
+/// A `cfg` set by a build script.
+///
+/// See <https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-cfg>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cfg<'a> {
+    /// `name`
+    Name(CowStr<'a>),
+    /// `name="value"`
+    KeyPair(CowStr<'a>, CowStr<'a>),
 }
 
-#[cfg(not(feature = "print"))]
-pub(crate) fn log_message(msg: &Message<'_>) {
-    match msg {
-        Message::BuildFinished(finished) => {
-            log::trace!("Build Finished: {:?}", finished.success);
+impl Cfg<'_> {
+    /// The name of the `cfg`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) | Self::KeyPair(name, _) => name.as_ref(),
         }
-        Message::CompilerArtifact(art) => {
-            log::trace!("Building {:#?}", art.package_id,);
+    }
+
+    /// The value of the `cfg`, if any
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::Name(_) => None,
+            Self::KeyPair(_, value) => Some(value.as_ref()),
         }
-        Message::CompilerMessage(comp) => {
-            let content = comp
-                .message
-                .rendered
-                .as_ref()
-                .map(|s| s.as_ref())
-                .unwrap_or_else(|| comp.message.message.as_ref());
-            match comp.message.level {
-                diagnostic::DiagnosticLevel::Ice => log::error!("{}", content),
-                diagnostic::DiagnosticLevel::Error => log::error!("{}", content),
-                diagnostic::DiagnosticLevel::Warning => log::warn!("{}", content),
-                diagnostic::DiagnosticLevel::Note => log::info!("{}", content),
-                diagnostic::DiagnosticLevel::Help => log::info!("{}", content),
-                #[cfg(not(feature = "strict_unstable"))]
-                _ => log::warn!("Unknown message: {:#?}", msg),
+    }
+}
+
+impl<'a> Cfg<'a> {
+    pub(crate) fn parse(raw: CowStr<'a>) -> Self {
+        let Some((name, value)) = raw.split_once('=') else {
+            return Self::Name(raw);
+        };
+        let value_start = name.len() + 1;
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(unquoted) if unquoted.contains('\\') => CowStr::Owned(unescape(unquoted)),
+            Some(unquoted) => sub_str(&raw, value_start + 1..value_start + 1 + unquoted.len()),
+            None => sub_str(&raw, value_start..raw.len()),
+        };
+        let name = sub_str(&raw, 0..name.len());
+        Self::KeyPair(name, value)
+    }
+}
+
+impl fmt::Display for Cfg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name}"),
+            Self::KeyPair(name, value) => {
+                write!(f, "{name}=\"")?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                write!(f, "\"")
             }
         }
-        Message::BuildScriptExecuted(script) => {
-            log::trace!("Ran script from {:#?}", script.package_id);
+    }
+}
+
+impl serde::Serialize for Cfg<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for Cfg<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = deserializer.deserialize_str(CowStrVisitor)?;
+        Ok(Self::parse(raw))
+    }
+}
+
+/// A library linked by a build script.
+///
+/// Format: `[KIND[:MODIFIERS]=]NAME`
+///
+/// See <https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-lib>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LinkedLib<'a> {
+    /// How the library is linked, `None` for rustc's default
+    pub kind: Option<LibKind<'a>>,
+    /// Linking modifiers, like `+whole-archive`
+    pub modifiers: Option<CowStr<'a>>,
+    /// Name of the library
+    pub name: CowStr<'a>,
+}
+
+impl<'a> LinkedLib<'a> {
+    fn parse(raw: CowStr<'a>) -> Result<Self, String> {
+        let Some((spec, _)) = raw.split_once('=') else {
+            return Ok(Self {
+                kind: None,
+                modifiers: None,
+                name: raw,
+            });
+        };
+        let name = sub_str(&raw, spec.len() + 1..raw.len());
+        let (kind, modifiers) = match spec.split_once(':') {
+            Some((kind, _)) => (
+                sub_str(&raw, 0..kind.len()),
+                Some(sub_str(&raw, kind.len() + 1..spec.len())),
+            ),
+            None => (sub_str(&raw, 0..spec.len()), None),
+        };
+        let kind = LibKind::parse(kind)?;
+        Ok(Self {
+            kind: Some(kind),
+            modifiers,
+            name,
+        })
+    }
+}
+
+impl fmt::Display for LinkedLib<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(kind) = &self.kind {
+            write!(f, "{kind}")?;
+            if let Some(modifiers) = &self.modifiers {
+                write!(f, ":{modifiers}")?;
+            }
+            write!(f, "=")?;
         }
-        #[cfg(not(feature = "strict_unstable"))]
-        _ => {
-            log::warn!("Unknown message: {:#?}", msg);
+        write!(f, "{}", self.name)
+    }
+}
+
+impl serde::Serialize for LinkedLib<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for LinkedLib<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = deserializer.deserialize_str(CowStrVisitor)?;
+        Self::parse(raw).map_err(serde::de::Error::custom)
+    }
+}
+
+/// How a [`LinkedLib`] is linked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LibKind<'a> {
+    /// `static`
+    Static,
+    /// `dylib`
+    Dylib,
+    /// `framework`
+    Framework,
+    #[doc(hidden)]
+    Unknown(CowStr<'a>),
+}
+
+impl<'a> LibKind<'a> {
+    fn parse(raw: CowStr<'a>) -> Result<Self, String> {
+        match raw.as_ref() {
+            "static" => Ok(Self::Static),
+            "dylib" => Ok(Self::Dylib),
+            "framework" => Ok(Self::Framework),
+            #[cfg(not(feature = "strict_unstable"))]
+            _ => Ok(Self::Unknown(raw)),
+            #[cfg(feature = "strict_unstable")]
+            _ => Err(std::format!("unknown library kind `{raw}`")),
         }
     }
 }
 
-#[cfg(feature = "print")]
-#[allow(clippy::print_stderr)]
-pub(crate) fn log_message(msg: &Message<'_>) {
-    match msg {
-        Message::BuildFinished(finished) => {
-            eprintln!("Build Finished: {:?}", finished.success);
+impl fmt::Display for LibKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Static => write!(f, "static"),
+            Self::Dylib => write!(f, "dylib"),
+            Self::Framework => write!(f, "framework"),
+            Self::Unknown(raw) => write!(f, "{raw}"),
         }
-        Message::CompilerArtifact(art) => {
-            eprintln!("Building {:#?}", art.package_id,);
+    }
+}
+
+/// A library search path added by a build script.
+///
+/// Format: `[KIND=]PATH`
+///
+/// See <https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-search>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LinkedPath<'a> {
+    /// What the path is searched for
+    pub kind: SearchKind,
+    /// The directory to search
+    pub path: CowPath<'a>,
+}
+
+impl<'a> LinkedPath<'a> {
+    fn parse(raw: CowStr<'a>) -> Self {
+        let kind = raw
+            .split_once('=')
+            .and_then(|(kind, _)| SearchKind::parse(kind).map(|k| (k, kind.len() + 1)));
+        let (kind, path) = match kind {
+            Some((kind, start)) => (kind, sub_str(&raw, start..raw.len())),
+            None => (SearchKind::All, raw),
+        };
+        let path = match path {
+            borrow::Cow::Borrowed(path) => borrow::Cow::Borrowed(path::Path::new(path)),
+            borrow::Cow::Owned(path) => borrow::Cow::Owned(path.into()),
+        };
+        Self { kind, path }
+    }
+}
+
+impl fmt::Display for LinkedPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SearchKind::All => write!(f, "{}", self.path.display()),
+            kind => write!(f, "{}={}", kind.as_str(), self.path.display()),
         }
-        Message::CompilerMessage(comp) => {
-            let content = comp
-                .message
-                .rendered
-                .as_ref()
-                .map(|s| s.as_ref())
-                .unwrap_or_else(|| comp.message.message.as_ref());
-            match comp.message.level {
-                diagnostic::DiagnosticLevel::Ice => eprintln!("{content}"),
-                diagnostic::DiagnosticLevel::Error => eprintln!("{content}"),
-                diagnostic::DiagnosticLevel::Warning => eprintln!("{content}"),
-                diagnostic::DiagnosticLevel::Note => eprintln!("{content}"),
-                diagnostic::DiagnosticLevel::Help => eprintln!("{content}"),
-                #[cfg(not(feature = "strict_unstable"))]
-                _ => eprintln!("Unknown message: {:#?}", msg),
-            }
+    }
+}
+
+impl serde::Serialize for LinkedPath<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for LinkedPath<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = deserializer.deserialize_str(CowStrVisitor)?;
+        Ok(Self::parse(raw))
+    }
+}
+
+/// What a [`LinkedPath`] is searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchKind {
+    /// `native`, only for native libraries
+    Native,
+    /// `dependency`, only for dependencies
+    Dependency,
+    /// `crate`, only for direct dependencies
+    Crate,
+    /// `framework`, only for macOS frameworks
+    Framework,
+    /// `all` or unspecified
+    All,
+}
+
+impl SearchKind {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "native" => Some(Self::Native),
+            "dependency" => Some(Self::Dependency),
+            "crate" => Some(Self::Crate),
+            "framework" => Some(Self::Framework),
+            "all" => Some(Self::All),
+            _ => None,
         }
-        Message::BuildScriptExecuted(script) => {
-            eprintln!("Ran script from {:#?}", script.package_id);
+    }
+
+    /// The kind as passed to rustc
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Dependency => "dependency",
+            Self::Crate => "crate",
+            Self::Framework => "framework",
+            Self::All => "all",
         }
-        #[cfg(not(feature = "strict_unstable"))]
-        _ => {
-            eprintln!("Unknown message: {:#?}", msg);
+    }
+}
+
+/// An environment variable set by a build script.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct EnvVar<'a> {
+    /// Name of the variable
+    pub key: CowStr<'a>,
+    /// Value of the variable
+    pub value: CowStr<'a>,
+}
+
+impl serde::Serialize for EnvVar<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&(&self.key, &self.value), serializer)
+    }
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for EnvVar<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw<'a>(
+            #[serde(borrow)] BorrowedStr<'a>,
+            #[serde(borrow)] BorrowedStr<'a>,
+        );
+
+        let Raw(key, value) = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Self {
+            key: key.0,
+            value: value.0,
+        })
+    }
+}
+
+struct BorrowedStr<'a>(CowStr<'a>);
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for BorrowedStr<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(CowStrVisitor).map(Self)
+    }
+}
+
+struct CowStrVisitor;
+
+impl<'de> serde::de::Visitor<'de> for CowStrVisitor {
+    type Value = CowStr<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string")
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(CowStr::Borrowed(v))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(CowStr::Owned(v.to_owned()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(CowStr::Owned(v))
+    }
+}
+
+fn sub_str<'a>(raw: &CowStr<'a>, range: ops::Range<usize>) -> CowStr<'a> {
+    match raw {
+        borrow::Cow::Borrowed(raw) => borrow::Cow::Borrowed(&raw[range]),
+        borrow::Cow::Owned(raw) => borrow::Cow::Owned(raw[range].to_owned()),
+    }
+}
+
+fn unescape(raw: &str) -> String {
+    let mut unescaped = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(c) = chars.next() {
+                unescaped.push(c);
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// How long a unit took to compile.
+///
+/// Emitted by cargo versions supporting `-Zunstable-options --timings=json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct TimingInfo<'a> {
+    /// The workspace member this unit belongs to
+    #[serde(borrow)]
+    pub package_id: WorkspaceMember<'a>,
+    /// The cargo target (lib, bin, example, etc.) that was compiled
+    #[serde(borrow)]
+    pub target: Target<'a>,
+    /// What was done with the target (`build`, `test`, `check`, etc)
+    #[serde(borrow)]
+    pub mode: CowStr<'a>,
+    /// Total time spent compiling the unit
+    #[serde(with = "secs")]
+    pub duration: time::Duration,
+    /// Time until the unit's metadata was available for dependents
+    #[serde(default)]
+    #[serde(with = "opt_secs")]
+    pub rmeta_time: Option<time::Duration>,
+}
+
+mod secs {
+    use std::time;
+
+    pub(crate) fn serialize<S>(value: &time::Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64(value.as_secs_f64())
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<time::Duration, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let secs = <f64 as serde::Deserialize<'_>>::deserialize(deserializer)?;
+        time::Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
+
+mod opt_secs {
+    use std::time;
+
+    pub(crate) fn serialize<S>(
+        value: &Option<time::Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_some(&value.as_secs_f64()),
+            None => serializer.serialize_none(),
         }
     }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<time::Duration>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let secs = <Option<f64> as serde::Deserialize<'_>>::deserialize(deserializer)?;
+        secs.map(time::Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_script_executed() {
+        let input = r#"{"reason":"build-script-executed","package_id":"path+file:///tmp/bs#0.1.0","linked_libs":["static:+whole-archive=m","dylib=c","z"],"linked_paths":["native=/tmp","/usr/lib"],"cfgs":["has_foo","foo=\"bar\"","quoted=\"a\\\"b\""],"env":[["FOO","bar=baz"]],"out_dir":"/tmp/bs/out"}"#;
+        let msg: Message<'_> = serde_json::from_str(input).unwrap();
+        let Message::BuildScriptExecuted(script) = &msg else {
+            panic!("unexpected message: {msg:?}");
+        };
+
+        let cfgs: Vec<_> = script.cfgs.iter().map(|c| (c.name(), c.value())).collect();
+        assert_eq!(
+            cfgs,
+            [
+                ("has_foo", None),
+                ("foo", Some("bar")),
+                ("quoted", Some(r#"a"b"#))
+            ]
+        );
+
+        assert_eq!(script.linked_libs[0].kind, Some(LibKind::Static));
+        assert_eq!(
+            script.linked_libs[0].modifiers.as_deref(),
+            Some("+whole-archive")
+        );
+        assert_eq!(script.linked_libs[0].name, "m");
+        assert_eq!(script.linked_libs[1].kind, Some(LibKind::Dylib));
+        assert_eq!(script.linked_libs[2].kind, None);
+        assert_eq!(script.linked_libs[2].name, "z");
+
+        assert_eq!(script.linked_paths[0].kind, SearchKind::Native);
+        assert_eq!(script.linked_paths[0].path, path::Path::new("/tmp"));
+        assert_eq!(script.linked_paths[1].kind, SearchKind::All);
+        assert_eq!(script.linked_paths[1].path, path::Path::new("/usr/lib"));
+
+        assert_eq!(script.env[0].key, "FOO");
+        assert_eq!(script.env[0].value, "bar=baz");
+
+        let roundtrip = serde_json::to_string(&msg).unwrap();
+        let roundtrip: Message<'_> = serde_json::from_str(&roundtrip).unwrap();
+        assert_eq!(msg, roundtrip);
+    }
 }
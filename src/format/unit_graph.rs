@@ -0,0 +1,224 @@
+//! Unit dependency graph, as reported by `--unit-graph`.
+//!
+//! Required feature: `cargo_unstable` since the format parsed is unstable.
+//!
+//! See <https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#unit-graph>
+
+use std::path;
+
+/// The graph of compilation units cargo would build, without building them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct UnitGraph {
+    /// Version of the unit graph format
+    pub version: u32,
+    /// All units, referenced by their index
+    pub units: Vec<Unit>,
+    /// Indices of the units requested on the command-line
+    pub roots: Vec<usize>,
+}
+
+impl UnitGraph {
+    /// The units requested on the command-line.
+    pub fn root_units(&self) -> impl Iterator<Item = &Unit> {
+        self.roots.iter().filter_map(|i| self.units.get(*i))
+    }
+
+    /// The units `unit` directly depends on.
+    pub fn dependencies<'s>(&'s self, unit: &'s Unit) -> impl Iterator<Item = &'s Unit> {
+        unit.dependencies
+            .iter()
+            .filter_map(|d| self.units.get(d.index))
+    }
+}
+
+/// A single compilation unit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct Unit {
+    /// The package this unit belongs to
+    pub pkg_id: String,
+    /// The cargo target (lib, bin, example, etc.) being compiled
+    pub target: UnitTarget,
+    /// The profile settings used for this unit
+    pub profile: UnitProfile,
+    /// Target triple this unit is compiled for, `None` for the host
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// What is being done with the target
+    pub mode: UnitMode,
+    /// The enabled features for this unit
+    pub features: Vec<String>,
+    /// Whether this is a standard library unit (`-Zbuild-std`)
+    #[serde(default)]
+    pub is_std: bool,
+    /// Units this unit depends on
+    pub dependencies: Vec<UnitDependency>,
+}
+
+/// The cargo target (lib, bin, example, etc.) of a [`Unit`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct UnitTarget {
+    /// Kind of target ("bin", "example", "test", "bench", "lib", "custom-build")
+    pub kind: Vec<String>,
+    /// Crate types generated for this target
+    pub crate_types: Vec<String>,
+    /// Name as given in the `Cargo.toml` or generated from the file name
+    pub name: String,
+    /// Path to the main source file of the target
+    pub src_path: path::PathBuf,
+    /// Rust edition for this target
+    pub edition: String,
+    /// Whether this is documented by `cargo doc`
+    #[serde(default)]
+    pub doc: bool,
+    /// Whether this is a doctest or not
+    #[serde(default)]
+    pub doctest: bool,
+    /// Whether this is tested by `cargo test`
+    #[serde(default)]
+    pub test: bool,
+    /// This target is built only if these features are enabled.
+    #[serde(default)]
+    #[serde(rename = "required-features")]
+    pub required_features: Vec<String>,
+}
+
+/// Profile settings used to compile a [`Unit`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct UnitProfile {
+    /// Name of the profile (`dev`, `release`, or a custom profile)
+    pub name: String,
+    /// Optimization level. Possible values are 0-3, s or z.
+    pub opt_level: String,
+    /// Link-time optimization setting
+    pub lto: String,
+    /// Codegen backend, if overridden
+    #[serde(default)]
+    pub codegen_backend: Option<String>,
+    /// Number of codegen units, if overridden
+    #[serde(default)]
+    pub codegen_units: Option<u32>,
+    /// The amount of debug info.
+    #[serde(default)]
+    pub debuginfo: Option<UnitDebugInfo>,
+    /// How debuginfo is split from the executable
+    #[serde(default)]
+    pub split_debuginfo: Option<String>,
+    /// State of the `cfg(debug_assertions)` directive
+    pub debug_assertions: bool,
+    /// State of the overflow checks.
+    pub overflow_checks: bool,
+    /// Whether rpath is set
+    pub rpath: bool,
+    /// Whether incremental compilation is enabled
+    pub incremental: bool,
+    /// Panic strategy
+    pub panic: String,
+    /// Symbol stripping, in cargo's internal representation
+    #[serde(default)]
+    pub strip: Option<serde_json::Value>,
+}
+
+/// The amount of debug info. 0 for none, 1 for limited, 2 for full
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum UnitDebugInfo {
+    /// 0 for none, 1 for limited, 2 for full
+    Level(u32),
+    /// none, limited, full, etc
+    Name(String),
+}
+
+/// What is being done with a [`Unit`]'s target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnitMode {
+    /// Compile a test harness
+    Test,
+    /// Compile normally
+    Build,
+    /// Only type-check
+    Check,
+    /// Document
+    Doc,
+    /// Compile and run doctests
+    Doctest,
+    /// Scrape examples for documentation
+    Docscrape,
+    /// Run a build script
+    RunCustomBuild,
+    #[cfg(not(feature = "strict_unstable"))]
+    #[doc(hidden)]
+    #[serde(other)]
+    Unknown,
+}
+
+impl UnitMode {
+    /// The mode as cargo names it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Test => "test",
+            Self::Build => "build",
+            Self::Check => "check",
+            Self::Doc => "doc",
+            Self::Doctest => "doctest",
+            Self::Docscrape => "docscrape",
+            Self::RunCustomBuild => "run-custom-build",
+            #[cfg(not(feature = "strict_unstable"))]
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// An edge in the [`UnitGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict_unstable", serde(deny_unknown_fields))]
+#[non_exhaustive]
+pub struct UnitDependency {
+    /// Index of the dependency in [`UnitGraph::units`]
+    pub index: usize,
+    /// Name the dependency is referred to by in the dependent crate
+    pub extern_crate_name: String,
+    /// Whether this is a public dependency
+    #[serde(default)]
+    pub public: bool,
+    /// Whether the dependency is excluded from the prelude
+    #[serde(default)]
+    pub noprelude: bool,
+    /// Whether unused dependency warnings are suppressed
+    #[serde(default)]
+    pub nounused: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph() {
+        let input = r#"{"version":1,"units":[{"pkg_id":"path+file:///tmp/dep#0.1.0","target":{"kind":["lib"],"crate_types":["lib"],"name":"dep","src_path":"/tmp/dep/src/lib.rs","edition":"2018","doc":true,"doctest":true,"test":true},"profile":{"name":"release","opt_level":"3","lto":"false","codegen_backend":null,"codegen_units":null,"debuginfo":0,"split_debuginfo":null,"debug_assertions":false,"overflow_checks":false,"rpath":false,"incremental":false,"panic":"unwind","strip":{"resolved":{"Named":"debuginfo"}}},"platform":null,"mode":"build","features":[],"dependencies":[{"index":1,"extern_crate_name":"lazy_static","public":false,"noprelude":false,"nounused":false}]},{"pkg_id":"registry+https://github.com/rust-lang/crates.io-index#lazy_static@1.5.1","target":{"kind":["lib"],"crate_types":["lib"],"name":"lazy_static","src_path":"/tmp/lazy_static/src/lib.rs","edition":"2015","doc":true,"doctest":true,"test":true},"profile":{"name":"release","opt_level":"3","lto":"false","codegen_backend":null,"codegen_units":null,"debuginfo":0,"split_debuginfo":null,"debug_assertions":false,"overflow_checks":false,"rpath":false,"incremental":false,"panic":"unwind","strip":{"resolved":{"Named":"debuginfo"}}},"platform":null,"mode":"build","features":[],"dependencies":[]}],"roots":[0]}"#;
+        let data: UnitGraph = serde_json::from_str(input).unwrap();
+        let root = data.root_units().next().unwrap();
+        assert_eq!(root.target.name, "dep");
+        let deps: Vec<_> = data
+            .dependencies(root)
+            .map(|u| u.target.name.as_str())
+            .collect();
+        assert_eq!(deps, ["lazy_static"]);
+    }
+
+    #[test]
+    fn run_custom_build() {
+        let input = r#"{"pkg_id":"path+file:///tmp/script#0.1.0","target":{"kind":["custom-build"],"crate_types":["bin"],"name":"build-script-build","src_path":"/tmp/script/build.rs","edition":"2018","doc":false,"doctest":false,"test":false},"profile":{"name":"dev","opt_level":"0","lto":"false","codegen_backend":null,"codegen_units":null,"debuginfo":2,"split_debuginfo":null,"debug_assertions":true,"overflow_checks":false,"rpath":false,"incremental":false,"panic":"unwind","strip":{"deferred":"None"}},"platform":null,"mode":"run-custom-build","features":[],"dependencies":[{"index":1,"extern_crate_name":"build_script_build","public":false,"noprelude":false,"nounused":false}]}"#;
+        let data: Unit = serde_json::from_str(input).unwrap();
+        assert_eq!(data.mode, UnitMode::RunCustomBuild);
+    }
+}
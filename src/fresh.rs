@@ -0,0 +1,150 @@
+use std::fs;
+use std::path;
+use std::time;
+
+use crate::baseline::Fnv1a;
+use crate::format;
+use crate::run::CargoRun;
+
+/// The last [`CargoRun`] for a build configuration, valid while its inputs are unchanged.
+///
+/// Inputs are the build configuration, including the environment and toolchain, the files of the
+/// package (skipping `target` and hidden directories), along with `Cargo.lock` and
+/// `.cargo/config.toml` in the package's ancestors.
+///
+/// The fingerprint is persisted, so it uses a hash that is stable across Rust versions.
+pub(crate) struct Fingerprint {
+    record_dir: path::PathBuf,
+    config: u64,
+    hash: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record<'a> {
+    fingerprint: u64,
+    bin_path: path::PathBuf,
+    bin_stamp: Stamp,
+    profile_name: String,
+    #[serde(borrow)]
+    profile: format::ArtifactProfile<'a>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    len: u64,
+    modified: u128,
+}
+
+impl Stamp {
+    fn of(path: &path::Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(time::UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(Self {
+            len: metadata.len(),
+            modified,
+        })
+    }
+}
+
+impl Fingerprint {
+    /// Fingerprint the inputs for building `package_dir` with `config`, like the command line and
+    /// toolchain version.
+    pub(crate) fn new(config: &str, package_dir: &path::Path, target_dir: &path::Path) -> Self {
+        let mut hasher = Fnv1a::default();
+        hasher.write(config.as_bytes());
+        let config = hasher.finish();
+
+        let package_dir = fs::canonicalize(package_dir).unwrap_or_else(|_| package_dir.to_owned());
+        let mut files = Vec::new();
+        let skipped_dir = fs::canonicalize(target_dir).unwrap_or_else(|_| target_dir.to_owned());
+        collect_files(&package_dir, &skipped_dir, &mut files);
+        for dir in package_dir.ancestors() {
+            files.push(dir.join("Cargo.lock"));
+            files.push(dir.join(".cargo").join("config.toml"));
+            files.push(dir.join(".cargo").join("config"));
+        }
+        files.sort();
+        for file in files {
+            if let Some(stamp) = Stamp::of(&file) {
+                hasher.write(file.as_os_str().as_encoded_bytes());
+                hasher.write(&[0]);
+                hasher.write(&stamp.len.to_le_bytes());
+                hasher.write(&stamp.modified.to_le_bytes());
+            }
+        }
+
+        Self {
+            record_dir: target_dir.join("escargot"),
+            config,
+            hash: hasher.finish(),
+        }
+    }
+
+    fn record_path(&self) -> path::PathBuf {
+        self.record_dir.join(format!("{:016x}.json", self.config))
+    }
+
+    /// The recorded [`CargoRun`], if nothing changed since it was built.
+    pub(crate) fn lookup(&self) -> Option<CargoRun> {
+        let raw = fs::read_to_string(self.record_path()).ok()?;
+        let record: Record<'_> = serde_json::from_str(&raw).ok()?;
+        if record.fingerprint != self.hash || Stamp::of(&record.bin_path)? != record.bin_stamp {
+            return None;
+        }
+        log::debug!("Reusing fresh {}", record.bin_path.display());
+        Some(CargoRun::with_parts(
+            record.bin_path,
+            record.profile_name,
+            record.profile.into_owned(),
+        ))
+    }
+
+    /// Remember `run` for later [`Fingerprint::lookup`]s.
+    pub(crate) fn record(&self, run: &CargoRun) {
+        let Some(bin_stamp) = Stamp::of(run.path()) else {
+            return;
+        };
+        let record = Record {
+            fingerprint: self.hash,
+            bin_path: run.path().to_owned(),
+            bin_stamp,
+            profile_name: run.profile_name().to_owned(),
+            profile: run.profile().clone(),
+        };
+        // Recording is only an optimization, so failures only cost a later rebuild
+        let written = fs::create_dir_all(&self.record_dir).and_then(|()| {
+            let raw = serde_json::to_vec(&record)?;
+            fs::write(self.record_path(), raw)
+        });
+        if let Err(err) = written {
+            log::debug!("Not recording {}: {err}", self.record_path().display());
+        }
+    }
+}
+
+fn collect_files(dir: &path::Path, target_dir: &path::Path, files: &mut Vec<path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            let skip =
+                name == "target" || name.to_string_lossy().starts_with('.') || path == target_dir;
+            if !skip {
+                collect_files(&path, target_dir, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path;
+
+use crate::error::{CargoError, CargoResult, ErrorKind};
+
+/// Variables stripped by [`CargoBuild::isolated`][crate::CargoBuild::isolated].
+const STRIPPED_VARS: &[&str] = &[
+    "CARGO_HOME",
+    "CARGO_INCREMENTAL",
+    "CARGO_CACHE_RUSTC_INFO",
+    "CARGO_LOG",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "CARGO_ENCODED_RUSTDOCFLAGS",
+    "RUSTC",
+    "RUSTC_WRAPPER",
+    "RUSTC_WORKSPACE_WRAPPER",
+    "RUSTC_BOOTSTRAP",
+    "RUSTDOC",
+    "RUSTFLAGS",
+    "RUSTDOCFLAGS",
+];
+
+/// Prefixes of variables stripped by [`CargoBuild::isolated`][crate::CargoBuild::isolated].
+const STRIPPED_PREFIXES: &[&str] = &[
+    "CARGO_ALIAS_",
+    "CARGO_BUILD_",
+    "CARGO_HTTP_",
+    "CARGO_NET_",
+    "CARGO_PROFILE_",
+    "CARGO_REGISTRIES_",
+    "CARGO_REGISTRY_",
+    "CARGO_TARGET_",
+    "CARGO_TERM_",
+    "CARGO_UNSTABLE_",
+];
+
+/// Whether `key` influences how cargo or rustc build.
+pub(crate) fn is_stripped(key: &OsStr) -> bool {
+    let Some(key) = key.to_str() else {
+        return false;
+    };
+    STRIPPED_VARS.contains(&key) || STRIPPED_PREFIXES.iter().any(|p| key.starts_with(p))
+}
+
+/// The `CARGO_HOME` of the current process.
+pub(crate) fn ambient_home() -> Option<path::PathBuf> {
+    if let Some(home) = env::var_os("CARGO_HOME") {
+        return Some(home.into());
+    }
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| path::Path::new(&home).join(".cargo"))
+}
+
+/// The registry of the current process's `CARGO_HOME`, if `home` shares it.
+pub(crate) fn shared_registry(home: &path::Path) -> Option<path::PathBuf> {
+    let shared = ambient_home()?.join("registry");
+    (shared.is_dir() && shared != home.join("registry")).then_some(shared)
+}
+
+/// Create `home`, sharing the downloaded crates and index of the current process's `CARGO_HOME`.
+///
+/// Only `registry/cache` and `registry/index` are linked, so crates are unpacked into `home`.
+/// Builds sharing them are `--offline`, so nothing is downloaded into the linked directories,
+/// though cargo may still update the index's cache files.
+pub(crate) fn prepare_home(home: &path::Path) -> CargoResult<()> {
+    let registry = home.join("registry");
+    fs::create_dir_all(&registry).map_err(|e| {
+        CargoError::new(ErrorKind::InvalidCommand)
+            .set_context(format!("Failed to create `CARGO_HOME` {}", home.display()))
+            .set_cause(e)
+    })?;
+
+    let Some(shared) = shared_registry(home) else {
+        return Ok(());
+    };
+    for dir in ["cache", "index"] {
+        let original = shared.join(dir);
+        let link = registry.join(dir);
+        if !original.is_dir() || fs::symlink_metadata(&link).is_ok() {
+            continue;
+        }
+        // Sharing is only an optimization, so fall back to an empty cache
+        if let Err(err) = link_dir(&original, &link) {
+            log::debug!("Not sharing registry {}: {err}", original.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn link_dir(original: &path::Path, link: &path::Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn link_dir(original: &path::Path, link: &path::Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(original, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn link_dir(_original: &path::Path, _link: &path::Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stripped() {
+        assert!(is_stripped(OsStr::new("RUSTFLAGS")));
+        assert!(is_stripped(OsStr::new("CARGO_TARGET_DIR")));
+        assert!(is_stripped(OsStr::new("CARGO_BUILD_JOBS")));
+        assert!(!is_stripped(OsStr::new("RUSTUP_TOOLCHAIN")));
+        assert!(!is_stripped(OsStr::new("CARGO_MANIFEST_DIR")));
+        assert!(!is_stripped(OsStr::new("PATH")));
+    }
+}
@@ -4,6 +4,9 @@
 //!
 //! Features:
 //! - `print` for logged output to be printed instead, generally for test writing.
+//! - `tracing` for a `TracingReporter` and spans around each cargo invocation.
+//! - `lsp` for converting diagnostics to Language Server Protocol types.
+//! - `cargo_unstable` for APIs relying on unstable cargo features, like `--unit-graph`.
 //!
 //! ## Why escargot
 //!
@@ -42,21 +45,49 @@
 #[macro_use]
 extern crate serde;
 
+mod baseline;
+pub use crate::baseline::*;
 mod build;
 pub use crate::build::*;
+mod cache;
+pub use crate::cache::*;
 mod cargo;
 pub use crate::cargo::*;
+mod collector;
+pub use crate::collector::*;
+mod config;
+pub use crate::config::*;
+mod explain;
+pub use crate::explain::*;
+mod fresh;
+mod isolation;
 mod msg;
 pub use crate::msg::*;
+mod profile;
+mod report;
+pub use crate::report::*;
 mod run;
 pub use crate::run::*;
+mod shell;
+mod summary;
+pub use crate::summary::*;
+mod target;
+pub use crate::target::*;
+mod timings;
+pub use crate::timings::*;
+mod version;
+pub use crate::version::*;
 #[cfg(feature = "test_unstable")]
 mod test;
 #[cfg(feature = "test_unstable")]
 pub use test::*;
 
+pub mod annotation;
 pub mod error;
+pub mod fixture;
 pub mod format;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
@@ -0,0 +1,447 @@
+//! Convert diagnostics to the [Language Server Protocol][lsp].
+//!
+//! Required feature: `lsp`
+//!
+//! [lsp]: https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/
+//!
+//! # Example
+//!
+//! ```rust
+//! # let target_dir = tempfile::TempDir::new().unwrap();
+//! let root = std::env::current_dir().unwrap().join("tests/testsuite/fixtures/warn");
+//! let msgs = escargot::CargoBuild::new()
+//!     .manifest_path(root.join("Cargo.toml"))
+//!     .target_dir(target_dir.path())
+//!     .exec()
+//!     .unwrap();
+//! let msgs: Vec<_> = msgs.map(|m| m.unwrap()).collect();
+//! let diagnostics: Vec<_> = msgs
+//!     .iter()
+//!     .filter_map(|m| match m.decode().unwrap() {
+//!         escargot::format::Message::CompilerMessage(msg) => Some(msg.message),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! let converter = escargot::lsp::Converter::new(&root);
+//! for params in converter.publish(&diagnostics) {
+//!     println!("{}", serde_json::to_string(&params).unwrap());
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path;
+
+use crate::code_url;
+use crate::format::diagnostic as rustc;
+
+/// Convert rustc diagnostics, resolving relative paths against the workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converter {
+    root: path::PathBuf,
+}
+
+impl Converter {
+    /// Resolve relative paths against `workspace_root`.
+    pub fn new(workspace_root: impl AsRef<path::Path>) -> Self {
+        Self {
+            root: workspace_root.as_ref().to_owned(),
+        }
+    }
+
+    /// Group `diagnostics` by the document their primary span is in.
+    ///
+    /// Diagnostics without a primary span, like "aborting due to previous error", are skipped.
+    pub fn publish(&self, diagnostics: &[rustc::Diagnostic<'_>]) -> Vec<PublishDiagnosticsParams> {
+        let mut documents = BTreeMap::<String, Vec<Diagnostic>>::new();
+        for diagnostic in diagnostics {
+            if let Some((uri, diagnostic)) = self.diagnostic(diagnostic) {
+                documents.entry(uri).or_default().push(diagnostic);
+            }
+        }
+        documents
+            .into_iter()
+            .map(|(uri, diagnostics)| PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            })
+            .collect()
+    }
+
+    /// Convert `diagnostic`, returning the URI of the document it belongs to.
+    pub fn diagnostic(&self, diagnostic: &rustc::Diagnostic<'_>) -> Option<(String, Diagnostic)> {
+        let primary = diagnostic.primary_span()?;
+        let uri = self.uri(&primary.file_name);
+
+        let mut message = diagnostic.message.as_ref().to_owned();
+        let mut related = Vec::new();
+        for span in diagnostic.spans.iter().filter(|s| !s.is_primary) {
+            if let Some(label) = &span.label {
+                related.push(DiagnosticRelatedInformation {
+                    location: self.location(span),
+                    message: label.as_ref().to_owned(),
+                });
+            }
+        }
+        for child in &diagnostic.children {
+            let child_message = format!("{}: {}", level_name(child.level), child.message);
+            match child.primary_span().or(child.spans.first()) {
+                Some(span) => related.push(DiagnosticRelatedInformation {
+                    location: self.location(span),
+                    message: child_message,
+                }),
+                None => {
+                    message.push('\n');
+                    message.push_str(&child_message);
+                }
+            }
+        }
+
+        let code = diagnostic.code.as_ref().map(|c| c.code.as_ref().to_owned());
+        let code_description = code
+            .as_deref()
+            .and_then(code_url)
+            .map(|href| CodeDescription { href });
+        let diagnostic = Diagnostic {
+            range: range(primary),
+            severity: severity(diagnostic.level),
+            code,
+            code_description,
+            source: Some("rustc".to_owned()),
+            message,
+            related_information: (!related.is_empty()).then_some(related),
+        };
+        Some((uri, diagnostic))
+    }
+
+    /// Code actions applying the suggested replacements of `diagnostic`'s children.
+    pub fn quick_fixes(&self, diagnostic: &rustc::Diagnostic<'_>) -> Vec<CodeAction> {
+        let Some((_, converted)) = self.diagnostic(diagnostic) else {
+            return Vec::new();
+        };
+        let mut actions = Vec::new();
+        for child in &diagnostic.children {
+            let mut changes = BTreeMap::<String, Vec<TextEdit>>::new();
+            let mut preferred = true;
+            for span in &child.spans {
+                let Some(replacement) = &span.suggested_replacement else {
+                    continue;
+                };
+                preferred &=
+                    span.suggestion_applicability == Some(rustc::Applicability::MachineApplicable);
+                changes
+                    .entry(self.uri(&span.file_name))
+                    .or_default()
+                    .push(TextEdit {
+                        range: range(span),
+                        new_text: replacement.as_ref().to_owned(),
+                    });
+            }
+            if !changes.is_empty() {
+                actions.push(CodeAction {
+                    title: child.message.as_ref().to_owned(),
+                    kind: "quickfix".to_owned(),
+                    diagnostics: vec![converted.clone()],
+                    edit: WorkspaceEdit { changes },
+                    is_preferred: preferred,
+                });
+            }
+        }
+        actions
+    }
+
+    fn location(&self, span: &rustc::DiagnosticSpan<'_>) -> Location {
+        Location {
+            uri: self.uri(&span.file_name),
+            range: range(span),
+        }
+    }
+
+    fn uri(&self, file: &path::Path) -> String {
+        file_uri(&self.root.join(file))
+    }
+}
+
+/// Parameters of a `textDocument/publishDiagnostics` notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PublishDiagnosticsParams {
+    /// The document the diagnostics are for
+    pub uri: String,
+    /// The diagnostics in the document
+    pub diagnostics: Vec<Diagnostic>,
+    /// The version of the document, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+}
+
+/// An LSP diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// Where the diagnostic applies
+    pub range: Range,
+    /// How severe the diagnostic is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<DiagnosticSeverity>,
+    /// The lint or error code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Documentation for the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_description: Option<CodeDescription>,
+    /// What produced the diagnostic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The diagnostic's message
+    pub message: String,
+    /// Other locations relevant to the diagnostic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_information: Option<Vec<DiagnosticRelatedInformation>>,
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticSeverity {
+    /// Reports an error
+    Error,
+    /// Reports a warning
+    Warning,
+    /// Reports information
+    Information,
+    /// Reports a hint
+    Hint,
+}
+
+impl serde::Serialize for DiagnosticSeverity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self {
+            Self::Error => 1,
+            Self::Warning => 2,
+            Self::Information => 3,
+            Self::Hint => 4,
+        };
+        serializer.serialize_u8(value)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DiagnosticSeverity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            1 => Ok(Self::Error),
+            2 => Ok(Self::Warning),
+            3 => Ok(Self::Information),
+            4 => Ok(Self::Hint),
+            value => Err(serde::de::Error::custom(format!(
+                "invalid severity `{value}`"
+            ))),
+        }
+    }
+}
+
+/// A link to documentation for a [`Diagnostic::code`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CodeDescription {
+    /// URL of the documentation
+    pub href: String,
+}
+
+/// Another location relevant to a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DiagnosticRelatedInformation {
+    /// Where the information applies
+    pub location: Location,
+    /// The information
+    pub message: String,
+}
+
+/// A range within a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Location {
+    /// The document
+    pub uri: String,
+    /// The range within the document
+    pub range: Range,
+}
+
+/// A range within a document, with UTF-16 character offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Range {
+    /// Inclusive start
+    pub start: Position,
+    /// Exclusive end
+    pub end: Position,
+}
+
+/// A position within a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Position {
+    /// 0-based line
+    pub line: u32,
+    /// 0-based offset within the line, in UTF-16 code units
+    pub character: u32,
+}
+
+/// A change that fixes a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CodeAction {
+    /// Describes the change
+    pub title: String,
+    /// Always `quickfix`
+    pub kind: String,
+    /// The diagnostic being fixed
+    pub diagnostics: Vec<Diagnostic>,
+    /// The change
+    pub edit: WorkspaceEdit,
+    /// Whether the fix can be applied without review
+    pub is_preferred: bool,
+}
+
+/// Edits across documents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct WorkspaceEdit {
+    /// Edits, by document
+    pub changes: BTreeMap<String, Vec<TextEdit>>,
+}
+
+/// Replace a range of a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TextEdit {
+    /// What to replace
+    pub range: Range,
+    /// The replacement
+    pub new_text: String,
+}
+
+fn severity(level: rustc::DiagnosticLevel) -> Option<DiagnosticSeverity> {
+    match level {
+        rustc::DiagnosticLevel::Ice | rustc::DiagnosticLevel::Error => {
+            Some(DiagnosticSeverity::Error)
+        }
+        rustc::DiagnosticLevel::Warning => Some(DiagnosticSeverity::Warning),
+        rustc::DiagnosticLevel::Note => Some(DiagnosticSeverity::Information),
+        rustc::DiagnosticLevel::Help => Some(DiagnosticSeverity::Hint),
+        #[cfg(not(feature = "strict_unstable"))]
+        _ => None,
+    }
+}
+
+fn level_name(level: rustc::DiagnosticLevel) -> &'static str {
+    match level {
+        rustc::DiagnosticLevel::Ice => "internal compiler error",
+        rustc::DiagnosticLevel::Error => "error",
+        rustc::DiagnosticLevel::Warning => "warning",
+        rustc::DiagnosticLevel::Note => "note",
+        rustc::DiagnosticLevel::Help => "help",
+        #[cfg(not(feature = "strict_unstable"))]
+        _ => "note",
+    }
+}
+
+fn range(span: &rustc::DiagnosticSpan<'_>) -> Range {
+    let start_line = span.text.first().map(|l| l.text.as_ref());
+    let end_line = span.text.last().map(|l| l.text.as_ref());
+    Range {
+        start: position(span.line_start, span.column_start, start_line),
+        end: position(span.line_end, span.column_end, end_line),
+    }
+}
+
+/// Convert 1-based line and character column to 0-based line and UTF-16 offset.
+fn position(line: usize, column: usize, text: Option<&str>) -> Position {
+    let chars = column.saturating_sub(1);
+    let character = match text {
+        Some(text) => {
+            let utf16: usize = text.chars().take(chars).map(char::len_utf16).sum();
+            // Columns past the end of the line, like for a missing `;`
+            utf16 + chars.saturating_sub(text.chars().count())
+        }
+        None => chars,
+    };
+    Position {
+        line: u32::try_from(line.saturating_sub(1)).unwrap_or(u32::MAX),
+        character: u32::try_from(character).unwrap_or(u32::MAX),
+    }
+}
+
+/// A `file://` URI for an absolute `path`.
+fn file_uri(path: &path::Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive letters
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                uri.push(char::from(byte));
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MISMATCH: &str = r#"{"rendered":null,"$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"help","message":"try using a conversion method","rendered":null,"spans":[{"file_name":"src/main.rs","byte_start":0,"byte_end":0,"line_start":2,"line_end":2,"column_start":22,"column_end":22,"is_primary":true,"text":[{"text":"    let s: String = \"héllo\";","highlight_start":22,"highlight_end":22}],"label":null,"suggested_replacement":".to_string()","suggestion_applicability":"MachineApplicable","expansion":null}]},{"children":[],"code":null,"level":"note","message":"no spans here","rendered":null,"spans":[]}],"code":{"code":"E0308","explanation":null},"level":"error","message":"mismatched types","spans":[{"file_name":"src/main.rs","byte_start":0,"byte_end":0,"line_start":2,"line_end":2,"column_start":21,"column_end":28,"is_primary":true,"text":[{"text":"    let s: String = \"h😀llo\";","highlight_start":21,"highlight_end":28}],"label":"expected `String`, found `&str`","suggested_replacement":null,"suggestion_applicability":null,"expansion":null},{"file_name":"src/main.rs","byte_start":0,"byte_end":0,"line_start":2,"line_end":2,"column_start":12,"column_end":18,"is_primary":false,"text":[{"text":"    let s: String = \"h😀llo\";","highlight_start":12,"highlight_end":18}],"label":"expected due to this","suggested_replacement":null,"suggestion_applicability":null,"expansion":null}]}"#;
+
+    #[test]
+    fn convert() {
+        let diagnostic: rustc::Diagnostic<'_> = serde_json::from_str(MISMATCH).unwrap();
+        let converter = Converter::new("/work space");
+        let (uri, converted) = converter.diagnostic(&diagnostic).unwrap();
+        assert_eq!(uri, "file:///work%20space/src/main.rs");
+        assert_eq!(converted.severity, Some(DiagnosticSeverity::Error));
+        assert_eq!(
+            converted.code_description.unwrap().href,
+            "https://doc.rust-lang.org/error_codes/E0308.html"
+        );
+        assert_eq!(converted.message, "mismatched types\nnote: no spans here");
+        // The emoji is two UTF-16 code units
+        assert_eq!(converted.range.start.line, 1);
+        assert_eq!(converted.range.start.character, 20);
+        assert_eq!(converted.range.end.character, 28);
+
+        let related = converted.related_information.unwrap();
+        let related: Vec<_> = related.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(
+            related,
+            [
+                "expected due to this",
+                "help: try using a conversion method"
+            ]
+        );
+    }
+
+    #[test]
+    fn quick_fix() {
+        let diagnostic: rustc::Diagnostic<'_> = serde_json::from_str(MISMATCH).unwrap();
+        let actions = Converter::new("/work").quick_fixes(&diagnostic);
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].is_preferred);
+        let edits = &actions[0].edit.changes["file:///work/src/main.rs"];
+        assert_eq!(edits[0].new_text, ".to_string()");
+        assert_eq!(edits[0].range.start.character, 21);
+
+        let json = serde_json::to_value(&actions[0]).unwrap();
+        assert_eq!(json["isPreferred"], true);
+        assert_eq!(json["diagnostics"][0]["severity"], 1);
+        assert_eq!(json["diagnostics"][0]["code"], "E0308", "{json}");
+    }
+}
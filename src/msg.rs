@@ -5,6 +5,8 @@ use std::process;
 
 use crate::error::{CargoError, CargoResult, ErrorKind};
 use crate::format;
+use crate::report;
+use crate::shell;
 
 /// Messages returned from a cargo sub-command.
 #[derive(Debug)]
@@ -13,6 +15,10 @@ pub struct CommandMessages(InnerCommandMessages);
 #[derive(Debug)]
 struct InnerCommandMessages {
     done: bool,
+    command_line: String,
+    reporter: report::ReporterSlot,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
     child: process::Child,
     stdout: io::BufReader<process::ChildStdout>,
     stderr: io::BufReader<process::ChildStderr>,
@@ -21,17 +27,26 @@ struct InnerCommandMessages {
 impl CommandMessages {
     /// Run the command, allowing iteration over ndjson messages.
     pub fn with_command(mut cmd: process::Command) -> CargoResult<Self> {
+        let command_line = shell::redacted_command_line(&cmd);
         let mut child = cmd
             .stdout(process::Stdio::piped())
             .stderr(process::Stdio::piped())
             .spawn()
-            .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+            .map_err(|e| {
+                CargoError::new(ErrorKind::InvalidCommand)
+                    .set_context(format!("Command: {command_line}"))
+                    .set_cause(e)
+            })?;
         let stdout = child.stdout.take().expect("piped above");
         let stdout = io::BufReader::new(stdout);
         let stderr = child.stderr.take().expect("piped above");
         let stderr = io::BufReader::new(stderr);
         let msgs = InnerCommandMessages {
             done: false,
+            command_line,
+            reporter: report::ReporterSlot::default(),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
             child,
             stdout,
             stderr,
@@ -39,10 +54,25 @@ impl CommandMessages {
         Ok(CommandMessages(msgs))
     }
 
+    pub(crate) fn with_reporter(mut self, reporter: report::ReporterSlot) -> Self {
+        self.0.reporter = reporter;
+        self
+    }
+
+    /// Report messages within `span`
+    #[cfg(feature = "tracing")]
+    pub(crate) fn with_span(mut self, span: tracing::Span) -> Self {
+        self.0.span = span;
+        self
+    }
+
     #[inline]
     fn next_msg(&mut self) -> CargoResult<Option<Message>> {
         #![allow(clippy::branches_sharing_code)]
 
+        #[cfg(feature = "tracing")]
+        let _span = self.0.span.clone().entered();
+
         let mut content = String::new();
         let len = self
             .0
@@ -50,7 +80,14 @@ impl CommandMessages {
             .read_line(&mut content)
             .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
         if 0 < len {
-            Ok(Some(Message(content)))
+            let msg = Message(content);
+            if let Some(reporter) = self.0.reporter.get() {
+                // Undecodable messages are reported to the consumer of the iterator
+                if let Ok(decoded) = msg.decode() {
+                    report::dispatch(reporter, &decoded);
+                }
+            }
+            Ok(Some(msg))
         } else {
             let status = self
                 .0
@@ -65,8 +102,11 @@ impl CommandMessages {
                     .stderr
                     .read_to_end(&mut data)
                     .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
-                let err = CargoError::new(ErrorKind::CommandFailed)
-                    .set_context(String::from_utf8_lossy(&data));
+                let err = CargoError::new(ErrorKind::CommandFailed).set_context(format!(
+                    "Command: {}\n{}",
+                    self.0.command_line,
+                    String::from_utf8_lossy(&data)
+                ));
                 Err(err)
             } else {
                 self.0.done = true;
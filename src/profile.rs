@@ -0,0 +1,89 @@
+use std::ffi::OsStr;
+
+/// Profiles cargo defines without any configuration.
+const BUILT_IN: &[&str] = &["dev", "release", "test", "bench"];
+
+/// Whether cargo defines `name` without any configuration.
+pub(crate) fn is_built_in(name: &str) -> bool {
+    BUILT_IN.contains(&name)
+}
+
+/// Whether the TOML document `toml` configures the `name` profile.
+///
+/// This looks for a `profile.<name>` key, like `[profile.name]`, `[profile.name.package.foo]`,
+/// or `profile.name.opt-level = 1`.  Inline tables, like `profile = { name = {} }`, aren't
+/// recognized.
+pub(crate) fn is_defined(toml: &str, name: &str) -> bool {
+    let mut table = String::new();
+    for line in toml.lines() {
+        let line = line.trim();
+        let key = if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            table = header.split(']').next().unwrap_or_default().to_owned();
+            table.clone()
+        } else if let Some((key, _)) = line.split_once('=') {
+            if table.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{table}.{key}")
+            }
+        } else {
+            continue;
+        };
+        let mut parts = key
+            .split('.')
+            .map(|part| part.trim().trim_matches(['"', '\'']));
+        if parts.next() == Some("profile") && parts.next() == Some(name) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether the environment variable `key` configures the `name` profile, like
+/// `CARGO_PROFILE_NAME_OPT_LEVEL`.
+pub(crate) fn is_defined_by_env(key: &OsStr, name: &str) -> bool {
+    let Some(key) = key.to_str() else {
+        return false;
+    };
+    let prefix = format!("CARGO_PROFILE_{}_", name.to_uppercase().replace('-', "_"));
+    key.starts_with(&prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml() {
+        let manifest = "\
+[package]
+name = \"foo\"
+
+[profile.release-lto]
+inherits = \"release\"
+lto = true
+";
+        assert!(is_defined(manifest, "release-lto"));
+        assert!(!is_defined(manifest, "lto"));
+        assert!(!is_defined(manifest, "foo"));
+
+        assert!(is_defined(
+            "[profile.\"ci\".package.foo]\nopt-level = 1",
+            "ci"
+        ));
+        assert!(is_defined("[profile]\nci.opt-level = 1", "ci"));
+        assert!(is_defined("profile.ci.inherits=\"dev\"", "ci"));
+        assert!(!is_defined("[build]\nprofile.ci = 1", "ci"));
+    }
+
+    #[test]
+    fn env() {
+        assert!(is_defined_by_env(
+            OsStr::new("CARGO_PROFILE_RELEASE_LTO_LTO"),
+            "release-lto"
+        ));
+        assert!(!is_defined_by_env(OsStr::new("CARGO_PROFILE_CI"), "ci"));
+        assert!(!is_defined_by_env(OsStr::new("CARGO_BUILD_JOBS"), "ci"));
+    }
+}
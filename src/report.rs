@@ -0,0 +1,299 @@
+use std::fmt;
+use std::hash;
+use std::sync;
+
+use crate::format;
+use crate::format::diagnostic::DiagnosticLevel;
+
+/// Observe progress while cargo builds.
+///
+/// Installed via [`CargoBuild::reporter`][crate::CargoBuild::reporter].  Without one,
+/// [`CargoBuild::run`][crate::CargoBuild::run] uses [`set_default_reporter`]'s, defaulting to
+/// [`LogReporter`], or [`PrintReporter`] with the `print` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct Count(AtomicUsize);
+///
+/// impl escargot::Reporter for Count {
+///     fn on_artifact(&self, _artifact: &escargot::format::Artifact<'_>) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// # let target_dir = tempfile::TempDir::new().unwrap();
+/// let count = Arc::new(Count::default());
+/// escargot::CargoBuild::new()
+///     .bin("bin")
+///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+///     .target_dir(target_dir.path())
+///     .reporter(count.clone())
+///     .run()
+///     .unwrap();
+/// assert_eq!(count.0.load(Ordering::Relaxed), 1);
+/// ```
+pub trait Reporter: Send + Sync {
+    /// A unit finished compiling
+    fn on_artifact(&self, artifact: &format::Artifact<'_>) {
+        let _ = artifact;
+    }
+
+    /// The compiler reported a diagnostic
+    fn on_diagnostic(&self, message: &format::FromCompiler<'_>) {
+        let _ = message;
+    }
+
+    /// A build script ran
+    fn on_build_script(&self, script: &format::BuildScript<'_>) {
+        let _ = script;
+    }
+
+    /// The build completed
+    fn on_finished(&self, finished: &format::BuildFinished) {
+        let _ = finished;
+    }
+
+    /// Any other message, like [`format::Message::TimingInfo`]
+    fn on_other(&self, msg: &format::Message<'_>) {
+        let _ = msg;
+    }
+}
+
+/// Report progress through the [`log`] crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogReporter;
+
+impl Reporter for LogReporter {
+    fn on_artifact(&self, artifact: &format::Artifact<'_>) {
+        log::trace!("Building {:#?}", artifact.package_id);
+    }
+
+    fn on_diagnostic(&self, message: &format::FromCompiler<'_>) {
+        let content = rendered(message);
+        match message.message.level {
+            DiagnosticLevel::Ice => log::error!("{content}"),
+            DiagnosticLevel::Error => log::error!("{content}"),
+            DiagnosticLevel::Warning => log::warn!("{content}"),
+            DiagnosticLevel::Note => log::info!("{content}"),
+            DiagnosticLevel::Help => log::info!("{content}"),
+            #[cfg(not(feature = "strict_unstable"))]
+            _ => log::warn!("Unknown message: {message:#?}"),
+        }
+    }
+
+    fn on_build_script(&self, script: &format::BuildScript<'_>) {
+        log::trace!("Ran script from {:#?}", script.package_id);
+    }
+
+    fn on_finished(&self, finished: &format::BuildFinished) {
+        log::trace!("Build Finished: {:?}", finished.success);
+    }
+
+    fn on_other(&self, msg: &format::Message<'_>) {
+        match msg {
+            format::Message::TimingInfo(timing) => {
+                log::trace!("Compiled {:#?} in {:?}", timing.package_id, timing.duration);
+            }
+            _ => log::warn!("Unknown message: {msg:#?}"),
+        }
+    }
+}
+
+/// Report progress to stderr, generally for test writing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PrintReporter;
+
+#[allow(clippy::print_stderr)]
+impl Reporter for PrintReporter {
+    fn on_artifact(&self, artifact: &format::Artifact<'_>) {
+        eprintln!("Building {:#?}", artifact.package_id);
+    }
+
+    fn on_diagnostic(&self, message: &format::FromCompiler<'_>) {
+        eprintln!("{}", rendered(message));
+    }
+
+    fn on_build_script(&self, script: &format::BuildScript<'_>) {
+        eprintln!("Ran script from {:#?}", script.package_id);
+    }
+
+    fn on_finished(&self, finished: &format::BuildFinished) {
+        eprintln!("Build Finished: {:?}", finished.success);
+    }
+
+    fn on_other(&self, msg: &format::Message<'_>) {
+        match msg {
+            format::Message::TimingInfo(timing) => {
+                eprintln!("Compiled {:#?} in {:?}", timing.package_id, timing.duration);
+            }
+            _ => eprintln!("Unknown message: {msg:#?}"),
+        }
+    }
+}
+
+/// Report progress as [`tracing`] events.
+///
+/// Diagnostics are events at the matching level with `level`, `code`, `file`, and `line`
+/// fields.  Build steps are `debug` events with a `package` field.
+///
+/// Required feature: `tracing`
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingReporter;
+
+#[cfg(feature = "tracing")]
+impl Reporter for TracingReporter {
+    fn on_artifact(&self, artifact: &format::Artifact<'_>) {
+        tracing::debug!(
+            package = artifact.package_id.as_str(),
+            target = artifact.target.name.as_ref(),
+            fresh = artifact.fresh,
+            "compiled"
+        );
+    }
+
+    fn on_diagnostic(&self, message: &format::FromCompiler<'_>) {
+        let diagnostic = &message.message;
+        let level = format!("{:?}", diagnostic.level).to_lowercase();
+        let code = diagnostic.code.as_ref().map(|c| c.code.as_ref());
+        let span = diagnostic.primary_span();
+        let file = span.map(|s| s.file_name.display().to_string());
+        let line = span.map(|s| s.line_start);
+        macro_rules! event {
+            ($event:ident) => {
+                tracing::$event!(
+                    package = message.package_id.as_str(),
+                    level,
+                    code,
+                    file,
+                    line,
+                    "{}",
+                    diagnostic.message
+                )
+            };
+        }
+        match diagnostic.level {
+            DiagnosticLevel::Ice | DiagnosticLevel::Error => event!(error),
+            DiagnosticLevel::Warning => event!(warn),
+            _ => event!(info),
+        }
+    }
+
+    fn on_build_script(&self, script: &format::BuildScript<'_>) {
+        tracing::debug!(package = script.package_id.as_str(), "ran build script");
+    }
+
+    fn on_finished(&self, finished: &format::BuildFinished) {
+        tracing::debug!(success = finished.success, "finished");
+    }
+
+    fn on_other(&self, msg: &format::Message<'_>) {
+        match msg {
+            format::Message::TimingInfo(timing) => {
+                tracing::debug!(
+                    package = timing.package_id.as_str(),
+                    target = timing.target.name.as_ref(),
+                    duration = ?timing.duration,
+                    "timing"
+                );
+            }
+            _ => tracing::warn!(message = ?msg, "unknown message"),
+        }
+    }
+}
+
+static DEFAULT_REPORTER: sync::RwLock<Option<sync::Arc<dyn Reporter>>> = sync::RwLock::new(None);
+
+/// Change the [`Reporter`] used when none is installed on a
+/// [`CargoBuild`][crate::CargoBuild].
+///
+/// # Example
+///
+/// ```rust
+/// escargot::set_default_reporter(std::sync::Arc::new(escargot::PrintReporter));
+/// ```
+pub fn set_default_reporter(reporter: sync::Arc<dyn Reporter>) {
+    *DEFAULT_REPORTER.write().unwrap_or_else(|e| e.into_inner()) = Some(reporter);
+}
+
+fn default_reporter() -> sync::Arc<dyn Reporter> {
+    if let Some(reporter) = DEFAULT_REPORTER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return reporter.clone();
+    }
+    #[cfg(not(feature = "print"))]
+    let reporter = LogReporter;
+    #[cfg(feature = "print")]
+    let reporter = PrintReporter;
+    sync::Arc::new(reporter)
+}
+
+fn rendered<'a>(message: &'a format::FromCompiler<'_>) -> &'a str {
+    message
+        .message
+        .rendered
+        .as_deref()
+        .unwrap_or_else(|| message.message.message.as_ref())
+}
+
+/// Dispatch `msg` to the matching [`Reporter`] callback.
+pub(crate) fn dispatch(reporter: &dyn Reporter, msg: &format::Message<'_>) {
+    match msg {
+        format::Message::CompilerArtifact(artifact) => reporter.on_artifact(artifact),
+        format::Message::CompilerMessage(message) => reporter.on_diagnostic(message),
+        format::Message::BuildScriptExecuted(script) => reporter.on_build_script(script),
+        format::Message::BuildFinished(finished) => reporter.on_finished(finished),
+        _ => reporter.on_other(msg),
+    }
+}
+
+/// An optional [`Reporter`], ignored when comparing or hashing build configurations.
+#[derive(Clone, Default)]
+pub(crate) struct ReporterSlot(Option<sync::Arc<dyn Reporter>>);
+
+impl ReporterSlot {
+    pub(crate) fn new(reporter: sync::Arc<dyn Reporter>) -> Self {
+        Self(Some(reporter))
+    }
+
+    pub(crate) fn get(&self) -> Option<&dyn Reporter> {
+        self.0.as_deref()
+    }
+
+    /// The installed reporter, falling back to the [`set_default_reporter`]
+    pub(crate) fn or_default(self) -> Self {
+        if self.0.is_some() {
+            return self;
+        }
+        Self::new(default_reporter())
+    }
+}
+
+impl fmt::Debug for ReporterSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(Reporter)"),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl PartialEq for ReporterSlot {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ReporterSlot {}
+
+impl hash::Hash for ReporterSlot {
+    fn hash<H: hash::Hasher>(&self, _state: &mut H) {}
+}
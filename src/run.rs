@@ -34,27 +34,34 @@ use crate::msg::CommandMessages;
 /// ```
 /// See [`CargoRun::path`] for how to then run the newly compiled
 /// program.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CargoRun {
     bin_path: path::PathBuf,
+    profile_name: String,
+    profile: format::ArtifactProfile<'static>,
 }
 
 impl CargoRun {
-    pub(crate) fn from_message(
-        msgs: CommandMessages,
-        is_bin: bool,
-        is_example: bool,
-    ) -> CargoResult<Self> {
-        let kind = match (is_bin, is_example) {
-            (true, true) => {
-                return Err(CargoError::new(ErrorKind::CommandFailed)
-                    .set_context("Ambiguous which binary is intended, multiple selected"));
-            }
-            (false, true) => "example",
-            _ => "bin",
-        };
-        let bin_path = extract_binary_path(msgs, kind)?;
-        Ok(Self { bin_path })
+    pub(crate) fn from_message(msgs: CommandMessages, kind: &'static str) -> CargoResult<Self> {
+        let (bin_path, profile) = extract_binary_path(msgs, kind)?;
+        let profile_name = profile_name(&bin_path);
+        Ok(Self {
+            bin_path,
+            profile_name,
+            profile,
+        })
+    }
+
+    pub(crate) fn with_parts(
+        bin_path: path::PathBuf,
+        profile_name: String,
+        profile: format::ArtifactProfile<'static>,
+    ) -> Self {
+        Self {
+            bin_path,
+            profile_name,
+            profile,
+        }
     }
 
     /// Path to the specified binary.
@@ -94,6 +101,35 @@ impl CargoRun {
         &self.bin_path
     }
 
+    /// Name of the profile the binary was built with, like `dev` or `release`.
+    ///
+    /// This comes from the directory cargo wrote the binary to, so it reflects how the profile
+    /// was selected, whether by [`CargoBuild::profile`][crate::CargoBuild::profile], `--release`,
+    /// or cargo config.  The `test` and `bench` profiles share the `dev` and `release`
+    /// directories, so they are reported as those.
+    pub fn profile_name(&self) -> &str {
+        self.profile_name.as_str()
+    }
+
+    /// Compiler settings the binary was built with.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let target_dir = tempfile::TempDir::new().unwrap();
+    /// let run = escargot::CargoBuild::new()
+    ///     .bin("bin")
+    ///     .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+    ///     .target_dir(target_dir.path())
+    ///     .run()
+    ///     .unwrap();
+    /// assert_eq!(run.profile_name(), "dev");
+    /// assert_eq!(run.profile().opt_level, "0");
+    /// ```
+    pub fn profile(&self) -> &format::ArtifactProfile<'static> {
+        &self.profile
+    }
+
     /// Run the build artifact.
     ///
     /// # Example
@@ -134,14 +170,18 @@ impl CargoRun {
     }
 }
 
-fn extract_bin<'a>(msg: &'a format::Message<'_>, desired_kind: &str) -> Option<&'a path::Path> {
+fn extract_bin(
+    msg: format::Message<'_>,
+    desired_kind: &str,
+) -> Option<(path::PathBuf, format::ArtifactProfile<'static>)> {
     match msg {
         format::Message::CompilerArtifact(art) => {
             if !art.profile.test
                 && art.target.crate_types == ["bin"]
                 && art.target.kind == [desired_kind]
             {
-                Some(art.filenames.first().expect("files must exist"))
+                let path = art.filenames.first().expect("files must exist");
+                Some((path.to_path_buf(), art.profile.into_owned()))
             } else {
                 None
             }
@@ -150,6 +190,22 @@ fn extract_bin<'a>(msg: &'a format::Message<'_>, desired_kind: &str) -> Option<&
     }
 }
 
+/// The profile `bin_path` was built with, from its output directory like `target/debug`.
+fn profile_name(bin_path: &path::Path) -> String {
+    let dir = bin_path.parent().and_then(|dir| match dir.file_name() {
+        Some(name) if name == "examples" => dir.parent(),
+        _ => Some(dir),
+    });
+    let name = dir
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match name.as_str() {
+        "debug" => "dev".to_owned(),
+        _ => name,
+    }
+}
+
 fn transpose<T, E>(r: Result<Option<T>, E>) -> Option<Result<T, E>> {
     match r {
         Ok(Some(x)) => Some(Ok(x)),
@@ -161,12 +217,11 @@ fn transpose<T, E>(r: Result<Option<T>, E>) -> Option<Result<T, E>> {
 fn extract_binary_paths(
     msgs: CommandMessages,
     kind: &'static str,
-) -> impl Iterator<Item = Result<path::PathBuf, CargoError>> {
+) -> impl Iterator<Item = Result<(path::PathBuf, format::ArtifactProfile<'static>), CargoError>> {
     msgs.filter_map(move |m| {
         let m = m.and_then(|m| {
             let m = m.decode()?;
-            format::log_message(&m);
-            let p = extract_bin(&m, kind).map(|p| p.to_path_buf());
+            let p = extract_bin(m, kind);
             Ok(p)
         });
         transpose(m)
@@ -176,14 +231,31 @@ fn extract_binary_paths(
 fn extract_binary_path(
     msgs: CommandMessages,
     kind: &'static str,
-) -> Result<path::PathBuf, CargoError> {
+) -> Result<(path::PathBuf, format::ArtifactProfile<'static>), CargoError> {
     let bins: Result<Vec<_>, CargoError> = extract_binary_paths(msgs, kind).collect();
     let bins = bins?;
     if bins.is_empty() {
         return Err(CargoError::new(ErrorKind::CommandFailed).set_context("No binaries in crate"));
     } else if bins.len() != 1 {
-        return Err(CargoError::new(ErrorKind::CommandFailed)
-            .set_context(std::format!("Ambiguous which binary is intended: {bins:?}")));
+        return Err(
+            CargoError::new(ErrorKind::CommandFailed).set_context(std::format!(
+                "Ambiguous which binary is intended: {:?}",
+                bins.iter().map(|(p, _)| p).collect::<Vec<_>>()
+            )),
+        );
     }
     Ok(bins.into_iter().next().expect("already validated"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names() {
+        let name = |p: &str| profile_name(path::Path::new(p));
+        assert_eq!(name("target/debug/bin"), "dev");
+        assert_eq!(name("target/release/examples/example"), "release");
+        assert_eq!(name("target/x86_64-unknown-linux-gnu/custom/bin"), "custom");
+    }
+}
@@ -0,0 +1,83 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::process;
+
+/// Render `cmd` as a POSIX shell command line, including its environment and working directory.
+pub(crate) fn command_line(cmd: &process::Command) -> String {
+    render(cmd, false)
+}
+
+/// Like [`command_line`] but with environment values hidden, for errors and logs.
+///
+/// Values passed via [`CargoBuild::env`][crate::CargoBuild::env] may be secrets, like tokens.
+pub(crate) fn redacted_command_line(cmd: &process::Command) -> String {
+    render(cmd, true)
+}
+
+fn render(cmd: &process::Command, redact: bool) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(dir) = cmd.get_current_dir() {
+        parts.push("cd".into());
+        parts.push(quote(dir.as_os_str()));
+        parts.push("&&".into());
+    }
+
+    let mut envs: Vec<_> = cmd.get_envs().collect();
+    envs.sort_unstable();
+    let removed: Vec<_> = envs.iter().filter(|(_, v)| v.is_none()).collect();
+    if !removed.is_empty() {
+        parts.push("env".into());
+        for (key, _) in removed {
+            parts.push("-u".into());
+            parts.push(quote(key));
+        }
+    }
+    for (key, value) in &envs {
+        if let Some(value) = value {
+            let value = if redact {
+                Cow::Borrowed("'***'")
+            } else {
+                quote(value)
+            };
+            parts.push(format!("{}={value}", key.to_string_lossy()).into());
+        }
+    }
+
+    parts.push(quote(cmd.get_program()));
+    parts.extend(cmd.get_args().map(quote));
+
+    parts.join(" ")
+}
+
+fn quote(arg: &OsStr) -> Cow<'_, str> {
+    let arg = arg.to_string_lossy();
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        let mut cmd = process::Command::new("cargo");
+        cmd.args(["build", "--features", "a b", "--config", "it's", ""])
+            .env("RUSTFLAGS", "-D warnings")
+            .env_remove("CARGO_TARGET_DIR")
+            .current_dir("/tmp/some dir");
+        assert_eq!(
+            command_line(&cmd),
+            r#"cd '/tmp/some dir' && env -u CARGO_TARGET_DIR RUSTFLAGS='-D warnings' cargo build --features 'a b' --config 'it'\''s' ''"#
+        );
+        assert_eq!(
+            redacted_command_line(&cmd),
+            r#"cd '/tmp/some dir' && env -u CARGO_TARGET_DIR RUSTFLAGS='***' cargo build --features 'a b' --config 'it'\''s' ''"#
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::format;
+use crate::format::diagnostic::DiagnosticLevel;
+
+/// Counts of what happened during a build, aggregated from [`format::Message`]s.
+///
+/// Renders as a text table via [`fmt::Display`] and serializes for machine consumption.
+///
+/// # Example
+///
+/// ```rust
+/// # let target_dir = tempfile::TempDir::new().unwrap();
+/// let mut summary = escargot::BuildSummary::new();
+/// let msgs = escargot::CargoBuild::new()
+///     .manifest_path("tests/testsuite/fixtures/warn/Cargo.toml")
+///     .target_dir(target_dir.path())
+///     .exec()
+///     .unwrap();
+/// for msg in msgs {
+///     let msg = msg.unwrap();
+///     summary.add(&msg.decode().unwrap());
+/// }
+/// assert_eq!(summary.success(), Some(true));
+/// assert_eq!(summary.warnings(), 2);
+/// println!("{summary}");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildSummary {
+    packages: BTreeMap<String, PackageSummary>,
+    success: Option<bool>,
+}
+
+impl BuildSummary {
+    /// Create an empty summary.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record `msg`.
+    ///
+    /// Returns whether the message was recorded.
+    pub fn add(&mut self, msg: &format::Message<'_>) -> bool {
+        match msg {
+            format::Message::CompilerArtifact(artifact) => {
+                let package = self.package(artifact.package_id.as_str());
+                if artifact.fresh {
+                    package.fresh += 1;
+                } else {
+                    package.compiled += 1;
+                }
+                true
+            }
+            format::Message::CompilerMessage(message) => {
+                let package = self.package(message.package_id.as_str());
+                match message.message.level {
+                    DiagnosticLevel::Ice | DiagnosticLevel::Error => package.errors += 1,
+                    DiagnosticLevel::Warning => package.warnings += 1,
+                    _ => return false,
+                }
+                true
+            }
+            format::Message::BuildScriptExecuted(script) => {
+                self.package(script.package_id.as_str()).build_scripts += 1;
+                true
+            }
+            format::Message::BuildFinished(finished) => {
+                self.success = Some(finished.success);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn package(&mut self, package_id: &str) -> &mut PackageSummary {
+        self.packages.entry(package_id.to_owned()).or_default()
+    }
+
+    /// Counts for each package, by package ID
+    pub fn packages(&self) -> impl Iterator<Item = (&str, &PackageSummary)> {
+        self.packages.iter().map(|(id, p)| (id.as_str(), p))
+    }
+
+    /// Units compiled by this build
+    pub fn compiled(&self) -> usize {
+        self.packages.values().map(|p| p.compiled).sum()
+    }
+
+    /// Units that were already up-to-date
+    pub fn fresh(&self) -> usize {
+        self.packages.values().map(|p| p.fresh).sum()
+    }
+
+    /// Warnings across all packages
+    pub fn warnings(&self) -> usize {
+        self.packages.values().map(|p| p.warnings).sum()
+    }
+
+    /// Errors across all packages
+    pub fn errors(&self) -> usize {
+        self.packages.values().map(|p| p.errors).sum()
+    }
+
+    /// Build scripts run across all packages
+    pub fn build_scripts(&self) -> usize {
+        self.packages.values().map(|p| p.build_scripts).sum()
+    }
+
+    /// Whether the build succeeded, if it finished
+    pub fn success(&self) -> Option<bool> {
+        self.success
+    }
+}
+
+impl fmt::Display for BuildSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.packages.keys().map(|id| package_name(id)).collect();
+        let width = names.iter().map(|n| n.len()).chain([7]).max().unwrap_or(0);
+        writeln!(
+            f,
+            "{:<width$}  {:>8}  {:>5}  {:>8}  {:>6}  {:>7}",
+            "Package", "Compiled", "Fresh", "Warnings", "Errors", "Scripts"
+        )?;
+        for (name, package) in names.iter().zip(self.packages.values()) {
+            writeln!(f, "{name:<width$}  {package}")?;
+        }
+        let total = PackageSummary {
+            compiled: self.compiled(),
+            fresh: self.fresh(),
+            warnings: self.warnings(),
+            errors: self.errors(),
+            build_scripts: self.build_scripts(),
+        };
+        writeln!(f, "{:<width$}  {total}", "Total")?;
+        match self.success {
+            Some(true) => writeln!(f, "Build succeeded"),
+            Some(false) => writeln!(f, "Build failed"),
+            None => writeln!(f, "Build did not finish"),
+        }
+    }
+}
+
+/// Counts of what happened to a single package.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PackageSummary {
+    /// Units compiled by this build
+    pub compiled: usize,
+    /// Units that were already up-to-date
+    pub fresh: usize,
+    /// Warnings reported
+    pub warnings: usize,
+    /// Errors reported
+    pub errors: usize,
+    /// Build scripts run
+    pub build_scripts: usize,
+}
+
+impl fmt::Display for PackageSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8}  {:>5}  {:>8}  {:>6}  {:>7}",
+            self.compiled, self.fresh, self.warnings, self.errors, self.build_scripts
+        )
+    }
+}
+
+/// Shorten a package ID, like `path+file:///tmp/foo#0.1.0` or `foo 0.1.0 (path+file:///tmp/foo)`,
+/// to `foo@0.1.0`.
+pub(crate) fn package_name(package_id: &str) -> String {
+    if let Some((source, fragment)) = package_id.rsplit_once('#') {
+        if fragment.contains('@') {
+            return fragment.to_owned();
+        }
+        let name = source
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(source);
+        return format!("{name}@{fragment}");
+    }
+    let mut parts = package_id.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(version)) => format!("{name}@{version}"),
+        _ => package_id.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate() {
+        let mut summary = BuildSummary::new();
+        for raw in [
+            r#"{"reason":"build-script-executed","package_id":"registry+https://github.com/rust-lang/crates.io-index#dep@1.0.0","linked_libs":[],"linked_paths":[],"cfgs":[],"env":[],"out_dir":"/tmp/out"}"#,
+            r#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#dep@1.0.0","manifest_path":"/tmp/dep/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"dep","src_path":"/tmp/dep/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":[],"executable":null,"fresh":true}"#,
+            r#"{"reason":"compiler-message","package_id":"path+file:///tmp/app#0.1.0","manifest_path":"/tmp/app/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"app","src_path":"/tmp/app/src/main.rs","edition":"2021","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: unused","$message_type":"diagnostic","children":[],"code":null,"level":"warning","message":"unused","spans":[]}}"#,
+            r#"{"reason":"compiler-artifact","package_id":"path+file:///tmp/app#0.1.0","manifest_path":"/tmp/app/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"app","src_path":"/tmp/app/src/main.rs","edition":"2021","doc":true,"doctest":false,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":[],"executable":null,"fresh":false}"#,
+            r#"{"reason":"build-finished","success":true}"#,
+        ] {
+            let msg: format::Message<'_> = serde_json::from_str(raw).unwrap();
+            assert!(summary.add(&msg), "{raw}");
+        }
+
+        assert_eq!(summary.compiled(), 1);
+        assert_eq!(summary.fresh(), 1);
+        assert_eq!(summary.warnings(), 1);
+        assert_eq!(summary.build_scripts(), 1);
+        assert_eq!(summary.success(), Some(true));
+        assert_eq!(
+            summary.to_string(),
+            "\
+Package    Compiled  Fresh  Warnings  Errors  Scripts
+app@0.1.0         1      0         1       0        0
+dep@1.0.0         0      1         0       0        1
+Total             1      1         1       0        1
+Build succeeded
+"
+        );
+
+        let json = serde_json::to_string(&summary).unwrap();
+        let roundtrip: BuildSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(summary, roundtrip);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(package_name("path+file:///tmp/foo#0.1.0"), "foo@0.1.0");
+        assert_eq!(package_name("path+file:///tmp/foo#bar@0.1.0"), "bar@0.1.0");
+        assert_eq!(
+            package_name("foo 0.1.0 (path+file:///tmp/foo)"),
+            "foo@0.1.0"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::borrow;
+use std::path;
+use std::process;
+
+use crate::error::{CargoError, CargoResult, ErrorKind};
+use crate::format::Cfg;
+
+/// Configuration of a compilation target, as reported by `rustc --print cfg`.
+///
+/// Created via [`Cargo::target_cfg`][crate::Cargo::target_cfg].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetCfg {
+    triple: String,
+    cfgs: Vec<Cfg<'static>>,
+}
+
+impl TargetCfg {
+    pub(crate) fn parse(triple: &str, raw: &str) -> Self {
+        let cfgs = raw
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| Cfg::parse(borrow::Cow::Owned(l.to_owned())))
+            .collect();
+        Self {
+            triple: triple.to_owned(),
+            cfgs,
+        }
+    }
+
+    /// The target triplet
+    pub fn triple(&self) -> &str {
+        self.triple.as_str()
+    }
+
+    /// `target_os`, like `linux` or `windows`
+    pub fn os(&self) -> Option<&str> {
+        self.value("target_os")
+    }
+
+    /// `target_arch`, like `x86_64` or `aarch64`
+    pub fn arch(&self) -> Option<&str> {
+        self.value("target_arch")
+    }
+
+    /// `target_env`, like `gnu` or `msvc`
+    pub fn env(&self) -> Option<&str> {
+        self.value("target_env")
+    }
+
+    /// `target_vendor`, like `unknown` or `apple`
+    pub fn vendor(&self) -> Option<&str> {
+        self.value("target_vendor")
+    }
+
+    /// `target_endian`, either `little` or `big`
+    pub fn endian(&self) -> Option<&str> {
+        self.value("target_endian")
+    }
+
+    /// `target_pointer_width`, in bits
+    pub fn pointer_width(&self) -> Option<u32> {
+        self.value("target_pointer_width")
+            .and_then(|w| w.parse().ok())
+    }
+
+    /// `target_family`, like `unix` or `wasm`
+    pub fn families(&self) -> impl Iterator<Item = &str> {
+        self.values("target_family")
+    }
+
+    /// `target_feature`s enabled by default, like `sse2`
+    pub fn features(&self) -> impl Iterator<Item = &str> {
+        self.values("target_feature")
+    }
+
+    /// Whether `cfg` is set for the target, like `("target_os", Some("linux"))` or `("unix", None)`
+    pub fn has_cfg(&self, name: &str, value: Option<&str>) -> bool {
+        self.cfgs
+            .iter()
+            .any(|c| c.name() == name && c.value() == value)
+    }
+
+    /// Every `cfg` set for the target
+    pub fn cfgs(&self) -> &[Cfg<'static>] {
+        &self.cfgs
+    }
+
+    fn value(&self, name: &'static str) -> Option<&str> {
+        self.values(name).next()
+    }
+
+    fn values(&self, name: &'static str) -> impl Iterator<Item = &str> {
+        self.cfgs
+            .iter()
+            .filter(move |c| c.name() == name)
+            .filter_map(|c| c.value())
+    }
+}
+
+/// Whether the standard library for `triple` is installed in `sysroot`.
+pub(crate) fn is_installed(sysroot: &path::Path, triple: &str) -> bool {
+    sysroot
+        .join("lib")
+        .join("rustlib")
+        .join(triple)
+        .join("lib")
+        .is_dir()
+}
+
+/// Run `rustc --print`, returning its output.
+pub(crate) fn print(mut rustc: process::Command, args: &[&str]) -> CargoResult<String> {
+    let output = rustc
+        .arg("--print")
+        .args(args)
+        .output()
+        .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+    if !output.status.success() {
+        return Err(CargoError::new(ErrorKind::CommandFailed)
+            .set_context(String::from_utf8_lossy(&output.stderr)));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linux() {
+        let input = r#"debug_assertions
+panic="unwind"
+target_abi=""
+target_arch="x86_64"
+target_endian="little"
+target_env="gnu"
+target_family="unix"
+target_feature="fxsr"
+target_feature="sse"
+target_feature="sse2"
+target_has_atomic="64"
+target_has_atomic="ptr"
+target_os="linux"
+target_pointer_width="64"
+target_vendor="unknown"
+unix
+"#;
+        let cfg = TargetCfg::parse("x86_64-unknown-linux-gnu", input);
+        assert_eq!(cfg.os(), Some("linux"));
+        assert_eq!(cfg.arch(), Some("x86_64"));
+        assert_eq!(cfg.env(), Some("gnu"));
+        assert_eq!(cfg.pointer_width(), Some(64));
+        assert_eq!(cfg.families().collect::<Vec<_>>(), ["unix"]);
+        assert_eq!(cfg.features().collect::<Vec<_>>(), ["fxsr", "sse", "sse2"]);
+        assert!(cfg.has_cfg("unix", None));
+        assert!(cfg.has_cfg("target_has_atomic", Some("ptr")));
+        assert!(!cfg.has_cfg("windows", None));
+    }
+}
@@ -38,6 +38,7 @@ pub struct CargoTest {
     bin_path: path::PathBuf,
     kind: String,
     name: String,
+    profile: format::ArtifactProfile<'static>,
 }
 
 impl CargoTest {
@@ -124,6 +125,11 @@ impl CargoTest {
         &self.bin_path
     }
 
+    /// Compiler settings the test was built with.
+    pub fn profile(&self) -> &format::ArtifactProfile<'static> {
+        &self.profile
+    }
+
     /// Run the build artifact.
     pub fn command(&self) -> process::Command {
         let mut cmd = process::Command::new(self.path());
@@ -137,7 +143,7 @@ impl CargoTest {
     }
 }
 
-fn extract_bin(msg: &format::Message<'_>) -> Option<CargoTest> {
+fn extract_bin(msg: format::Message<'_>) -> Option<CargoTest> {
     match msg {
         format::Message::CompilerArtifact(art) => {
             if art.profile.test {
@@ -154,10 +160,12 @@ fn extract_bin(msg: &format::Message<'_>) -> Option<CargoTest> {
                     .as_ref()
                     .to_owned();
                 let name = art.target.name.as_ref().to_owned();
+                let profile = art.profile.into_owned();
                 Some(CargoTest {
                     bin_path,
                     kind,
                     name,
+                    profile,
                 })
             } else {
                 None
@@ -181,8 +189,7 @@ fn extract_binary_paths(
     msgs.filter_map(move |m| {
         let m = m.and_then(|m| {
             let m = m.decode()?;
-            format::log_message(&m);
-            let p = extract_bin(&m);
+            let p = extract_bin(m);
             Ok(p)
         });
         transpose(m)
@@ -0,0 +1,326 @@
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time;
+
+use crate::format;
+
+/// Compile times aggregated from [`format::Message::TimingInfo`].
+///
+/// # Example
+///
+/// Messages are only emitted with `CargoBuild::timings_json`, which requires the
+/// `cargo_unstable` feature and a nightly cargo.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "cargo_unstable")] {
+/// let mut report = escargot::TimingReport::new();
+/// let msgs = escargot::CargoBuild::new()
+///     .manifest_path("tests/testsuite/fixtures/dep/Cargo.toml")
+///     .timings_json()
+///     .exec()
+///     .unwrap();
+/// for msg in msgs {
+///     let msg = msg.unwrap();
+///     report.add(&msg.decode().unwrap());
+/// }
+/// println!("{report}");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TimingReport {
+    units: Vec<UnitTiming>,
+}
+
+impl TimingReport {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record `msg` if it is a [`format::Message::TimingInfo`].
+    ///
+    /// Returns whether the message was recorded.
+    pub fn add(&mut self, msg: &format::Message<'_>) -> bool {
+        match msg {
+            format::Message::TimingInfo(timing) => {
+                self.units.push(UnitTiming::new(timing));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Every unit recorded, in the order they finished.
+    pub fn units(&self) -> &[UnitTiming] {
+        &self.units
+    }
+
+    /// Time spent across all units, ignoring parallelism.
+    pub fn total(&self) -> time::Duration {
+        self.units.iter().map(|u| u.duration).sum()
+    }
+
+    /// The `count` slowest units, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<&UnitTiming> {
+        let mut units: Vec<_> = self.units.iter().collect();
+        units.sort_by_key(|u| cmp::Reverse(u.duration));
+        units.truncate(count);
+        units
+    }
+
+    /// Time spent per package, slowest first.
+    pub fn by_package(&self) -> Vec<(&str, time::Duration)> {
+        let mut packages = BTreeMap::new();
+        for unit in &self.units {
+            *packages
+                .entry(unit.package_id.as_str())
+                .or_insert(time::Duration::ZERO) += unit.duration;
+        }
+        let mut packages: Vec<_> = packages.into_iter().collect();
+        packages.sort_by_key(|p| cmp::Reverse(p.1));
+        packages
+    }
+
+    /// The chain of dependent units with the longest combined compile time.
+    ///
+    /// Units in `graph` without timing information are treated as taking no time.  The path is
+    /// ordered from the root being built down to the first unit compiled.
+    ///
+    /// Required feature: `cargo_unstable` since the unit graph is unstable.
+    #[cfg(feature = "cargo_unstable")]
+    pub fn critical_path(&self, graph: &format::unit_graph::UnitGraph) -> Vec<&UnitTiming> {
+        fn longest(
+            index: usize,
+            graph: &format::unit_graph::UnitGraph,
+            timings: &[Option<usize>],
+            durations: &[time::Duration],
+            memo: &mut [Option<(time::Duration, Option<usize>)>],
+        ) -> time::Duration {
+            if let Some((total, _)) = memo[index] {
+                return total;
+            }
+            // Guard against cycles in malformed input
+            memo[index] = Some((time::Duration::ZERO, None));
+            let mut best = (time::Duration::ZERO, None);
+            for dep in &graph.units[index].dependencies {
+                if graph.units.len() <= dep.index {
+                    continue;
+                }
+                let total = longest(dep.index, graph, timings, durations, memo);
+                if best.1.is_none() || best.0 < total {
+                    best = (total, Some(dep.index));
+                }
+            }
+            let own = timings[index]
+                .map(|t| durations[t])
+                .unwrap_or(time::Duration::ZERO);
+            memo[index] = Some((own + best.0, best.1));
+            own + best.0
+        }
+
+        let timings: Vec<_> = graph
+            .units
+            .iter()
+            .map(|unit| self.units.iter().position(|t| t.is_unit(unit)))
+            .collect();
+        let durations: Vec<_> = self.units.iter().map(|u| u.duration).collect();
+        let mut memo = vec![None; graph.units.len()];
+
+        let mut start = None;
+        let mut start_total = time::Duration::ZERO;
+        for root in graph.roots.iter().copied() {
+            if graph.units.len() <= root {
+                continue;
+            }
+            let total = longest(root, graph, &timings, &durations, &mut memo);
+            if start.is_none() || start_total < total {
+                start = Some(root);
+                start_total = total;
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut next = start;
+        while let Some(index) = next {
+            if let Some(timing) = timings[index] {
+                path.push(&self.units[timing]);
+            }
+            next = memo[index].and_then(|(_, dep)| dep);
+        }
+        path
+    }
+}
+
+impl fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} units compiled in {:.2}s",
+            self.units.len(),
+            self.total().as_secs_f64()
+        )?;
+        for unit in self.slowest(self.units.len()) {
+            writeln!(f, "{:>8.2}s  {unit}", unit.duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// Compile time of a single unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitTiming {
+    package_id: String,
+    target: String,
+    kind: Vec<String>,
+    mode: String,
+    duration: time::Duration,
+    rmeta_time: Option<time::Duration>,
+}
+
+impl UnitTiming {
+    fn new(timing: &format::TimingInfo<'_>) -> Self {
+        Self {
+            package_id: timing.package_id.as_str().to_owned(),
+            target: timing.target.name.as_ref().to_owned(),
+            kind: timing
+                .target
+                .kind
+                .iter()
+                .map(|k| k.as_ref().to_owned())
+                .collect(),
+            mode: timing.mode.as_ref().to_owned(),
+            duration: timing.duration,
+            rmeta_time: timing.rmeta_time,
+        }
+    }
+
+    #[cfg(feature = "cargo_unstable")]
+    fn is_unit(&self, unit: &format::unit_graph::Unit) -> bool {
+        self.package_id == unit.pkg_id
+            && self.target == unit.target.name
+            && self.kind == unit.target.kind
+            && self.mode == unit.mode.as_str()
+    }
+
+    /// The package the unit belongs to
+    pub fn package_id(&self) -> &str {
+        self.package_id.as_str()
+    }
+
+    /// The name of the target compiled
+    pub fn target(&self) -> &str {
+        self.target.as_str()
+    }
+
+    /// What was done with the target (`build`, `test`, `check`, etc)
+    pub fn mode(&self) -> &str {
+        self.mode.as_str()
+    }
+
+    /// Total time spent compiling the unit
+    pub fn duration(&self) -> time::Duration {
+        self.duration
+    }
+
+    /// Time until the unit's metadata was available for dependents
+    pub fn rmeta_time(&self) -> Option<time::Duration> {
+        self.rmeta_time
+    }
+}
+
+impl fmt::Display for UnitTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.target, self.kind.join(", "))?;
+        if self.mode != "build" {
+            write!(f, ", {}", self.mode)?;
+        }
+        write!(f, ") from {}", self.package_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(name: &str, duration: f64) -> String {
+        format!(
+            r#"{{"reason":"timing-info","package_id":"path+file:///tmp/{name}#0.1.0","target":{{"kind":["lib"],"crate_types":["lib"],"name":"{name}","src_path":"/tmp/{name}/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true}},"mode":"build","duration":{duration},"rmeta_time":0.25}}"#
+        )
+    }
+
+    #[test]
+    fn aggregate() {
+        let mut report = TimingReport::new();
+        for raw in [
+            timing("fast", 0.5),
+            timing("slow", 2.0),
+            timing("fast", 1.0),
+        ] {
+            let msg: format::Message<'_> = serde_json::from_str(&raw).unwrap();
+            assert!(report.add(&msg));
+        }
+        let finished: format::Message<'_> =
+            serde_json::from_str(r#"{"reason":"build-finished","success":true}"#).unwrap();
+        assert!(!report.add(&finished));
+
+        assert_eq!(report.total(), time::Duration::from_millis(3500));
+        let slowest: Vec<_> = report.slowest(1).iter().map(|u| u.target()).collect();
+        assert_eq!(slowest, ["slow"]);
+        assert_eq!(
+            report.by_package(),
+            [
+                ("path+file:///tmp/slow#0.1.0", time::Duration::from_secs(2)),
+                (
+                    "path+file:///tmp/fast#0.1.0",
+                    time::Duration::from_millis(1500)
+                ),
+            ]
+        );
+        assert_eq!(
+            report.units()[0].rmeta_time(),
+            Some(time::Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cargo_unstable")]
+    fn critical_path() {
+        fn unit(name: &str, deps: &[usize]) -> String {
+            let deps: Vec<_> = deps
+                .iter()
+                .map(|i| format!(r#"{{"index":{i},"extern_crate_name":"dep{i}"}}"#))
+                .collect();
+            format!(
+                r#"{{"pkg_id":"path+file:///tmp/{name}#0.1.0","target":{{"kind":["lib"],"crate_types":["lib"],"name":"{name}","src_path":"/tmp/{name}/src/lib.rs","edition":"2021"}},"profile":{{"name":"dev","opt_level":"0","lto":"false","debug_assertions":true,"overflow_checks":true,"rpath":false,"incremental":true,"panic":"unwind"}},"mode":"build","features":[],"dependencies":[{}]}}"#,
+                deps.join(",")
+            )
+        }
+        let graph = format!(
+            r#"{{"version":1,"units":[{},{},{},{}],"roots":[0]}}"#,
+            unit("root", &[1, 2]),
+            unit("short", &[]),
+            unit("long", &[3]),
+            unit("leaf", &[]),
+        );
+        let graph: format::unit_graph::UnitGraph = serde_json::from_str(&graph).unwrap();
+
+        let mut report = TimingReport::new();
+        for raw in [
+            timing("leaf", 1.0),
+            timing("short", 1.5),
+            timing("long", 1.0),
+            timing("root", 0.5),
+        ] {
+            let msg: format::Message<'_> = serde_json::from_str(&raw).unwrap();
+            report.add(&msg);
+        }
+
+        let path: Vec<_> = report
+            .critical_path(&graph)
+            .iter()
+            .map(|u| u.target())
+            .collect();
+        assert_eq!(path, ["root", "long", "leaf"]);
+    }
+}
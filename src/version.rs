@@ -0,0 +1,330 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::ffi;
+use std::fmt;
+use std::process;
+use std::str;
+use std::sync;
+
+use crate::error::{CargoError, CargoResult, ErrorKind};
+
+/// Version information reported by `cargo -vV`.
+///
+/// Created via [`Cargo::version`][crate::Cargo::version].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CargoVersion {
+    /// The release version
+    pub version: Version,
+    /// The release channel
+    pub channel: Channel,
+    /// Git commit the release was built from
+    pub commit_hash: Option<String>,
+    /// Date of the git commit the release was built from
+    pub commit_date: Option<String>,
+    /// Target triplet cargo was built for
+    pub host: Option<String>,
+}
+
+impl CargoVersion {
+    pub(crate) fn parse(raw: &str) -> CargoResult<Self> {
+        let mut fields = VerboseVersion::parse(raw)?;
+        Ok(Self {
+            commit_hash: fields.take("commit-hash"),
+            commit_date: fields.take("commit-date"),
+            host: fields.take("host"),
+            version: fields.version,
+            channel: fields.channel,
+        })
+    }
+}
+
+/// Version information reported by `rustc -vV`.
+///
+/// Created via [`Cargo::rustc_version`][crate::Cargo::rustc_version].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RustcVersion {
+    /// The release version
+    pub version: Version,
+    /// The release channel
+    pub channel: Channel,
+    /// Git commit the release was built from
+    pub commit_hash: Option<String>,
+    /// Date of the git commit the release was built from
+    pub commit_date: Option<String>,
+    /// Target triplet rustc compiles for by default
+    pub host: Option<String>,
+    /// Version of LLVM rustc uses for code generation
+    pub llvm_version: Option<String>,
+}
+
+impl RustcVersion {
+    pub(crate) fn parse(raw: &str) -> CargoResult<Self> {
+        let mut fields = VerboseVersion::parse(raw)?;
+        Ok(Self {
+            commit_hash: fields.take("commit-hash"),
+            commit_date: fields.take("commit-date"),
+            host: fields.take("host"),
+            llvm_version: fields.take("LLVM version"),
+            version: fields.version,
+            channel: fields.channel,
+        })
+    }
+}
+
+/// A `MAJOR.MINOR.PATCH[-PRE]` release version.
+///
+/// Ordered by semver precedence, so `1.80.0-nightly` is less than `1.80.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Version {
+    /// Incremented for incompatible changes
+    pub major: u64,
+    /// Incremented for new features
+    pub minor: u64,
+    /// Incremented for fixes
+    pub patch: u64,
+    /// Pre-release identifier, like `nightly` or `beta.3`
+    pub pre: Option<String>,
+}
+
+impl Version {
+    /// Create a release version.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        let (release, pre) = match raw.split_once('-') {
+            Some((release, pre)) => (release, Some(pre.to_owned())),
+            None => (raw, None),
+        };
+        let mut parts = release.splitn(3, '.').map(|p| p.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next()??;
+        Some(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => cmp::Ordering::Equal,
+                (None, Some(_)) => cmp::Ordering::Greater,
+                (Some(_), None) => cmp::Ordering::Less,
+                (Some(s), Some(o)) => cmp_pre(s, o),
+            })
+    }
+}
+
+/// Compare pre-release identifiers by semver precedence, so `beta.9` is less than `beta.10`.
+fn cmp_pre(pre: &str, other: &str) -> cmp::Ordering {
+    let mut ids = pre.split('.');
+    let mut other_ids = other.split('.');
+    loop {
+        let ordering = match (ids.next(), other_ids.next()) {
+            (None, None) => return cmp::Ordering::Equal,
+            (None, Some(_)) => return cmp::Ordering::Less,
+            (Some(_), None) => return cmp::Ordering::Greater,
+            (Some(id), Some(other_id)) => match (id.parse::<u64>(), other_id.parse::<u64>()) {
+                (Ok(num), Ok(other_num)) => num.cmp(&other_num),
+                // Numeric identifiers have lower precedence than alphanumeric ones
+                (Ok(_), Err(_)) => cmp::Ordering::Less,
+                (Err(_), Ok(_)) => cmp::Ordering::Greater,
+                (Err(_), Err(_)) => id.cmp(other_id),
+            },
+        };
+        if ordering != cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// The release channel of a toolchain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Stable releases
+    Stable,
+    /// Beta releases
+    Beta,
+    /// Nightly releases
+    Nightly,
+    /// Locally built toolchains
+    Dev,
+}
+
+impl Channel {
+    fn from_version(version: &Version) -> Self {
+        match version.pre.as_deref() {
+            None => Self::Stable,
+            Some(pre) if pre.starts_with("beta") => Self::Beta,
+            Some("nightly") => Self::Nightly,
+            Some(_) => Self::Dev,
+        }
+    }
+}
+
+struct VerboseVersion {
+    version: Version,
+    channel: Channel,
+    fields: HashMap<String, String>,
+}
+
+impl VerboseVersion {
+    fn parse(raw: &str) -> CargoResult<Self> {
+        let fields: HashMap<_, _> = raw
+            .lines()
+            .skip(1)
+            .filter_map(|l| l.split_once(": "))
+            .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+            .collect();
+        let release = fields
+            .get("release")
+            .map(|r| r.as_str())
+            .or_else(|| raw.split_whitespace().nth(1))
+            .ok_or_else(|| {
+                CargoError::new(ErrorKind::InvalidOutput)
+                    .set_context(format!("No version found in `{raw}`"))
+            })?;
+        let version = Version::parse(release).ok_or_else(|| {
+            CargoError::new(ErrorKind::InvalidOutput)
+                .set_context(format!("Invalid version `{release}`"))
+        })?;
+        let channel = Channel::from_version(&version);
+        Ok(Self {
+            version,
+            channel,
+            fields,
+        })
+    }
+
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.fields.remove(key)
+    }
+}
+
+pub(crate) type VersionCache<T> = sync::Mutex<HashMap<Vec<ffi::OsString>, T>>;
+
+/// Run `cmd -vV`, caching the result by the program and its environment.
+pub(crate) fn query_cached<T: Clone>(
+    cache: &'static sync::OnceLock<VersionCache<T>>,
+    mut cmd: process::Command,
+    parse: fn(&str) -> CargoResult<T>,
+) -> CargoResult<T> {
+    let mut key = vec![cmd.get_program().to_owned()];
+    for (k, v) in cmd.get_envs() {
+        key.push(k.to_owned());
+        key.push(v.map(|v| v.to_owned()).unwrap_or_default());
+    }
+
+    let cache = cache.get_or_init(Default::default);
+    if let Some(version) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(version.clone());
+    }
+
+    let output = cmd
+        .arg("-vV")
+        .output()
+        .map_err(|e| CargoError::new(ErrorKind::InvalidCommand).set_cause(e))?;
+    if !output.status.success() {
+        return Err(CargoError::new(ErrorKind::CommandFailed)
+            .set_context(String::from_utf8_lossy(&output.stderr)));
+    }
+    let stdout = str::from_utf8(&output.stdout)
+        .map_err(|e| CargoError::new(ErrorKind::InvalidOutput).set_cause(e))?;
+    let version = parse(stdout)?;
+
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, version.clone());
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_stable() {
+        let input = "cargo 1.95.0 (f2d3ce0bd 2026-03-21)
+release: 1.95.0
+commit-hash: f2d3ce0bd7f24a49f8f72d9000448f8838c4e850
+commit-date: 2026-03-21
+host: x86_64-unknown-linux-gnu
+libgit2: 1.9.2 (sys:0.20.4 vendored)
+os: Debian 12.0.0 (bookworm) [64-bit]
+";
+        let version = CargoVersion::parse(input).unwrap();
+        assert_eq!(version.version, Version::new(1, 95, 0));
+        assert_eq!(version.channel, Channel::Stable);
+        assert_eq!(version.commit_date.as_deref(), Some("2026-03-21"));
+        assert_eq!(version.host.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    }
+
+    #[test]
+    fn rustc_nightly() {
+        let input = "rustc 1.97.0-nightly (4d1f98451 2026-05-15)
+binary: rustc
+commit-hash: 4d1f984518c77fad6eeef4f40153b002a659e662
+commit-date: 2026-05-15
+host: x86_64-unknown-linux-gnu
+release: 1.97.0-nightly
+LLVM version: 22.1.2
+";
+        let version = RustcVersion::parse(input).unwrap();
+        assert_eq!(version.version.to_string(), "1.97.0-nightly");
+        assert_eq!(version.channel, Channel::Nightly);
+        assert_eq!(version.llvm_version.as_deref(), Some("22.1.2"));
+    }
+
+    #[test]
+    fn short() {
+        let version = CargoVersion::parse("cargo 1.80.0-beta.3 (abcdef 2024-06-01)").unwrap();
+        assert_eq!(version.channel, Channel::Beta);
+        assert_eq!(version.commit_hash, None);
+    }
+
+    #[test]
+    fn ordering() {
+        let nightly = Version::parse("1.80.0-nightly").unwrap();
+        assert!(nightly < Version::new(1, 80, 0));
+        assert!(Version::new(1, 79, 2) < nightly);
+
+        let beta = |pre| Version::parse(&format!("1.80.0-{pre}")).unwrap();
+        assert!(beta("beta.9") < beta("beta.10"));
+        assert!(beta("beta") < beta("beta.1"));
+        assert!(beta("beta.2") < beta("nightly"));
+        assert!(beta("1") < beta("beta"));
+    }
+}
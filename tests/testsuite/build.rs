@@ -43,6 +43,53 @@ fn test_build_script() {
     test_fixture("script");
 }
 
+#[test]
+fn test_build_script_output() {
+    let temp = tempfile::TempDir::new().unwrap();
+
+    let msgs: Vec<_> = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/script/Cargo.toml")
+        .target_dir(temp.path())
+        .exec()
+        .unwrap()
+        .map(|msg| msg.unwrap())
+        .collect();
+    let script = msgs
+        .iter()
+        .find_map(|msg| match msg.decode().unwrap() {
+            escargot::format::Message::BuildScriptExecuted(script) => Some(script),
+            _ => None,
+        })
+        .unwrap();
+
+    let cfgs: Vec<_> = script.cfgs.iter().map(|c| (c.name(), c.value())).collect();
+    assert_eq!(
+        cfgs,
+        [("script_fixture", None), ("script_value", Some("fixture"))]
+    );
+    let env: Vec<_> = script
+        .env
+        .iter()
+        .map(|e| (e.key.as_ref(), e.value.as_ref()))
+        .collect();
+    assert_eq!(env, [("SCRIPT_FIXTURE", "1")]);
+    assert_eq!(script.linked_paths.len(), 1);
+    assert_eq!(
+        script.linked_paths[0].kind,
+        escargot::format::SearchKind::Native
+    );
+    assert_eq!(
+        Some(script.linked_paths[0].path.as_ref()),
+        script.out_dir.as_deref()
+    );
+    assert_eq!(script.linked_libs.len(), 1);
+    assert_eq!(
+        script.linked_libs[0].kind,
+        Some(escargot::format::LibKind::Dylib)
+    );
+    assert_eq!(script.linked_libs[0].name, "script_fixture");
+}
+
 #[test]
 fn test_dependency() {
     test_fixture("dep");
@@ -65,5 +112,217 @@ fn test_error() {
         println!("{msg:#?}");
     }
     assert!(msgs[error_idx].is_err());
-    println!("```{}```", msgs[error_idx].as_ref().err().unwrap());
+    let err = msgs[error_idx].as_ref().err().unwrap();
+    assert!(err.to_string().contains("--manifest-path"), "{err}");
+    println!("```{err}```");
+}
+
+#[test]
+fn test_clone_variants() {
+    let base = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .current_target()
+        .env("RUSTFLAGS", "-D warnings");
+    let release = base.clone().release();
+    assert_ne!(base, release);
+    assert!(!base.to_command_line().contains("--release"));
+    assert!(release.to_command_line().contains("--release"));
+
+    let target_dir = tempfile::TempDir::new().unwrap();
+    for variant in [base, release] {
+        let run = variant.target_dir(target_dir.path()).run().unwrap();
+        assert!(run.path().exists());
+    }
+}
+
+#[test]
+fn test_serde_roundtrip() {
+    let build = escargot::CargoBuild::new()
+        .package("bin")
+        .bin("bin")
+        .profile("bench")
+        .features("a b")
+        .env("RUSTFLAGS", "-D warnings")
+        .env_remove("CARGO_TARGET_DIR")
+        .arg("--quiet");
+    let json = serde_json::to_string(&build).unwrap();
+    let roundtrip: escargot::CargoBuild = serde_json::from_str(&json).unwrap();
+    assert_eq!(build, roundtrip);
+    assert_eq!(build.to_command_line(), roundtrip.to_command_line());
+
+    let partial: escargot::CargoBuild =
+        serde_json::from_str(r#"{"manifest_path": "Cargo.toml", "all_features": true}"#).unwrap();
+    assert!(
+        partial
+            .to_command_line()
+            .ends_with(" build --message-format=json --manifest-path Cargo.toml --all-features"),
+        "{}",
+        partial.to_command_line()
+    );
+}
+
+#[test]
+fn test_hermetic_config() {
+    let target_dir = tempfile::TempDir::new().unwrap();
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let config_file = config_dir.path().join("extra.toml");
+    std::fs::write(&config_file, "[profile.dev]\nopt-level = 1\n").unwrap();
+
+    let run = escargot::CargoBuild::new()
+        .current_dir("tests/testsuite/fixtures/bin")
+        .bin("bin")
+        .config("build.target-dir", target_dir.path())
+        .config_file(&config_file)
+        .offline()
+        .run()
+        .unwrap();
+    assert!(run.path().starts_with(target_dir.path()), "{:?}", run.path());
+    assert_eq!(run.profile().opt_level, "1");
+}
+
+#[test]
+fn test_isolated() {
+    let target_dir = tempfile::TempDir::new().unwrap();
+    let cargo_home = tempfile::TempDir::new().unwrap();
+    let home = cargo_home.path().join("home");
+
+    let build = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .bin("bin")
+        .target_dir(target_dir.path())
+        .isolated()
+        .cargo_home(&home)
+        .env("RUSTFLAGS", "-D warnings");
+    assert!(!build.stripped_env().iter().any(|k| k == "RUSTFLAGS"));
+    assert!(build.to_command_line().contains("CARGO_HOME="));
+
+    let ambient_home = std::env::var_os("CARGO_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| std::path::Path::new(&h).join(".cargo")));
+    let shared = ambient_home.is_some_and(|h| h.join("registry").is_dir());
+    assert_eq!(build.to_command_line().contains("--offline"), shared);
+
+    let run = build.run().unwrap();
+    assert!(run.path().exists());
+    let registry = std::fs::symlink_metadata(home.join("registry")).unwrap();
+    assert!(registry.is_dir());
+}
+
+#[test]
+fn test_cargo_home_into_command() {
+    let cargo_home = tempfile::TempDir::new().unwrap();
+    let home = cargo_home.path().join("home");
+
+    let cmd = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .cargo_home(&home)
+        .into_command();
+    assert!(cmd.get_envs().any(|(k, v)| k == "CARGO_HOME" && v == Some(home.as_os_str())));
+    assert!(home.join("registry").is_dir());
+}
+
+#[test]
+fn test_reporter() {
+    #[derive(Default)]
+    struct Collect(std::sync::Mutex<Vec<String>>);
+
+    impl escargot::Reporter for Collect {
+        fn on_diagnostic(&self, message: &escargot::format::FromCompiler<'_>) {
+            let text = message.message.message.to_string();
+            self.0.lock().unwrap().push(text);
+        }
+
+        fn on_finished(&self, finished: &escargot::format::BuildFinished) {
+            self.0.lock().unwrap().push(format!("finished={}", finished.success));
+        }
+    }
+
+    let target_dir = tempfile::TempDir::new().unwrap();
+    let collect = std::sync::Arc::new(Collect::default());
+    let count = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/warn/Cargo.toml")
+        .target_dir(target_dir.path())
+        .reporter(collect.clone())
+        .exec()
+        .unwrap()
+        .count();
+    let reported = collect.0.lock().unwrap();
+    assert!(1 < count);
+    assert!(reported.iter().any(|m| m.contains("missing documentation")), "{reported:?}");
+    assert_eq!(reported.last().map(|s| s.as_str()), Some("finished=true"));
+}
+
+#[test]
+#[cfg(feature = "tracing")]
+fn test_tracing_reporter() {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+
+    #[derive(Default)]
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    impl tracing::Subscriber for Collect {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("span {}", span.metadata().name()));
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0.join(" "));
+        }
+        fn enter(&self, _span: &tracing::span::Id) {}
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    let collect = Collect::default();
+    let target_dir = tempfile::TempDir::new().unwrap();
+    tracing::subscriber::with_default(collect.clone(), || {
+        let msgs = escargot::CargoBuild::new()
+            .manifest_path("tests/testsuite/fixtures/warn/Cargo.toml")
+            .target_dir(target_dir.path())
+            .reporter(Arc::new(escargot::TracingReporter))
+            .exec()
+            .unwrap();
+        for msg in msgs {
+            msg.unwrap();
+        }
+    });
+
+    let records = collect.0.lock().unwrap();
+    assert_eq!(records.first().map(|s| s.as_str()), Some("span cargo"));
+    assert!(
+        records
+            .iter()
+            .any(|r| r.contains("level=\"warning\"") && r.contains("code=\"missing_docs\"")),
+        "{records:#?}"
+    );
+}
+
+#[test]
+#[cfg(feature = "cargo_unstable")]
+fn test_timings_json() {
+    let html = escargot::CargoBuild::new().timings();
+    assert!(html.to_command_line().contains("--timings"));
+    assert!(!html.to_command_line().contains("--timings=json"));
+
+    let json = html.timings_json().to_command_line();
+    assert!(json.contains("-Zunstable-options --timings=json"), "{json}");
 }
@@ -0,0 +1,38 @@
+#[test]
+fn test_with_cargo_path() {
+    let temp = tempfile::TempDir::new().unwrap();
+
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let cmd = escargot::Cargo::with_cargo_path(cargo)
+        .build()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .target_dir(temp.path())
+        .run()
+        .unwrap();
+    let output = cmd.command().output().unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_toolchain_not_installed() {
+    let err = escargot::Cargo::toolchain("escargot-not-installed").unwrap_err();
+    assert_eq!(err.kind(), escargot::error::ErrorKind::ToolchainNotInstalled);
+}
+
+#[test]
+fn test_target_list() {
+    let cargo = escargot::Cargo::new();
+    let targets = cargo.target_list().unwrap();
+    assert!(targets.iter().any(|t| t == escargot::CURRENT_TARGET));
+    assert!(cargo.sysroot().unwrap().is_dir());
+    assert!(!cargo.is_target_installed("escargot-not-a-target").unwrap());
+}
+
+#[test]
+fn test_explain() {
+    let cargo = escargot::Cargo::new();
+    let explanation = cargo.explain("E0308").unwrap().unwrap();
+    assert!(explanation.contains("```"), "{explanation}");
+    assert_eq!(cargo.explain("E9999").unwrap(), None);
+    assert_eq!(cargo.explain("unused_variables").unwrap(), None);
+}
@@ -0,0 +1,27 @@
+#[test]
+fn test_workspace() {
+    let project = escargot::fixture::Project::new()
+        .manifest("[workspace]\nmembers = [\"crates/*\"]\nresolver = \"2\"\n")
+        .file(
+            "crates/app/Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\nlib = { path = \"../lib\" }\n",
+        )
+        .file("crates/app/src/main.rs", "fn main() { println!(\"{}\", lib::NAME); }\n")
+        .file(
+            "crates/lib/Cargo.toml",
+            "[package]\nname = \"lib\"\nversion = \"0.0.0\"\nedition = \"2021\"\n",
+        )
+        .file("crates/lib/src/lib.rs", "pub const NAME: &str = \"lib\";\n")
+        .create()
+        .unwrap();
+    let root = project.root().to_owned();
+    assert!(project.manifest_path().exists());
+
+    let run = project.build().package("app").run().unwrap();
+    assert!(run.path().starts_with(project.target_dir()));
+    let output = run.command().output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "lib\n");
+
+    drop(project);
+    assert!(!root.exists());
+}
@@ -1,3 +1,11 @@
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-cfg=script_fixture");
+    println!("cargo:rustc-cfg=script_value=\"fixture\"");
+    println!("cargo:rustc-env=SCRIPT_FIXTURE=1");
+    println!(
+        "cargo:rustc-link-search=native={}",
+        std::env::var("OUT_DIR").unwrap()
+    );
+    println!("cargo:rustc-link-lib=dylib=script_fixture");
 }
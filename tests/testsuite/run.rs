@@ -32,3 +32,170 @@ fn test_error() {
     assert!(result.is_err());
     println!("```{}```", result.err().unwrap());
 }
+
+#[test]
+fn test_undefined_profile() {
+    let temp = tempfile::TempDir::new().unwrap();
+
+    let result = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .profile("escargot-undefined")
+        .target_dir(temp.path())
+        .run();
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), escargot::error::ErrorKind::UndefinedProfile);
+    assert!(err.to_string().contains("escargot-undefined"), "{err}");
+}
+
+#[test]
+fn test_config_profile() {
+    let temp = tempfile::TempDir::new().unwrap();
+
+    let run = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .profile("escargot-config")
+        .config("profile.escargot-config.inherits", "release")
+        .target_dir(temp.path())
+        .run()
+        .unwrap();
+    assert_eq!(run.profile_name(), "escargot-config");
+    assert!(!run.profile().debug_assertions);
+}
+
+#[test]
+fn test_lib_not_runnable() {
+    let err = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin_lib/Cargo.toml")
+        .lib()
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("No binaries selected"), "{err}");
+}
+
+#[test]
+fn test_multiple_bins() {
+    let err = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .bin("bin")
+        .bin("other")
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("bin, other"), "{err}");
+}
+
+#[test]
+fn test_bin_glob() {
+    let temp = tempfile::TempDir::new().unwrap();
+
+    let cmd = escargot::CargoBuild::new()
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .bin("b*")
+        .target_dir(temp.path())
+        .run()
+        .unwrap();
+    let output = cmd.command().output().unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_run_cached() {
+    let target_dir = tempfile::TempDir::new().unwrap();
+    let build = escargot::CargoBuild::new()
+        .bin("bin")
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .target_dir(target_dir.path());
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let build = build.clone();
+            std::thread::spawn(move || build.run_cached().unwrap())
+        })
+        .collect();
+    let paths: Vec<_> = threads
+        .into_iter()
+        .map(|t| t.join().unwrap().path().to_owned())
+        .collect();
+    assert!(paths.iter().all(|p| *p == paths[0]));
+
+    std::fs::remove_file(&paths[0]).unwrap();
+    assert!(!build.clone().run_cached().unwrap().path().exists());
+    build.invalidate_cached();
+    assert!(build.run_cached().unwrap().path().exists());
+}
+
+#[test]
+fn test_skip_if_fresh() {
+    let project = tempfile::TempDir::new().unwrap();
+    let fixture = std::path::Path::new("tests/testsuite/fixtures/bin");
+    std::fs::create_dir_all(project.path().join("src")).unwrap();
+    for file in ["Cargo.toml", "Cargo.lock", "src/main.rs"] {
+        std::fs::copy(fixture.join(file), project.path().join(file)).unwrap();
+    }
+    let build = escargot::CargoBuild::new()
+        .bin("bin")
+        .manifest_path(project.path().join("Cargo.toml"))
+        .skip_if_fresh();
+
+    let run = build.clone().run().unwrap();
+    let record_dir = project.path().join("target").join("escargot");
+    assert_eq!(std::fs::read_dir(&record_dir).unwrap().count(), 1);
+    let output = run.command().output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello, world!\n");
+
+    let reused = build.clone().run().unwrap();
+    assert_eq!(run.path(), reused.path());
+
+    std::fs::write(
+        project.path().join("src/main.rs"),
+        "fn main() {\n    println!(\"Changed\");\n}\n",
+    )
+    .unwrap();
+    let rebuilt = build.run().unwrap();
+    let output = rebuilt.command().output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Changed\n");
+}
+
+#[test]
+fn test_skip_if_fresh_current_dir() {
+    let project = escargot::fixture::Project::new()
+        .file(
+            "src/main.rs",
+            "fn main() {\n    print!(\"{}\", include_str!(\"../message.txt\"));\n}\n",
+        )
+        .file("message.txt", "Hello\n")
+        .create()
+        .unwrap();
+    let target_dir = tempfile::TempDir::new().unwrap();
+    // Cargo finds the manifest above the current directory, along with `message.txt`
+    let build = escargot::CargoBuild::new()
+        .current_dir(project.root().join("src"))
+        .target_dir(target_dir.path())
+        .skip_if_fresh();
+
+    let run = build.clone().run().unwrap();
+    let output = run.command().output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello\n");
+    // The first build generates `Cargo.lock`, so the second records the lasting fingerprint
+    build.clone().run().unwrap();
+    let reused = build.clone().run().unwrap();
+    assert_eq!(run.path(), reused.path());
+
+    std::fs::write(project.root().join("message.txt"), "Changed\n").unwrap();
+    let rebuilt = build.run().unwrap();
+    let output = rebuilt.command().output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Changed\n");
+}
+
+#[test]
+fn test_profile_name() {
+    let target_dir = tempfile::TempDir::new().unwrap();
+    let run = escargot::CargoBuild::new()
+        .bin("bin")
+        .manifest_path("tests/testsuite/fixtures/bin/Cargo.toml")
+        .target_dir(target_dir.path())
+        .arg("--release")
+        .run()
+        .unwrap();
+    assert_eq!(run.profile_name(), "release");
+    assert!(!run.profile().debug_assertions);
+}